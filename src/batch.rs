@@ -0,0 +1,98 @@
+use std::future::Future;
+use std::io::Read;
+
+/// This function expands command-line arguments into a list of items (update aliases or build
+/// NVRs). The argument `-` is replaced with the items read from standard input, and arguments of
+/// the form `@FILE` are replaced with the items read from the file. Items in input are separated
+/// by whitespace, and lines starting with `#` are ignored.
+pub fn read_items(args: &[String]) -> Result<Vec<String>, String> {
+    let mut items = Vec::new();
+    let mut stdin_used = false;
+
+    for arg in args {
+        let contents = if arg == "-" {
+            if stdin_used {
+                return Err(String::from("Standard input can only be read once."));
+            }
+            stdin_used = true;
+
+            let mut contents = String::new();
+            std::io::stdin()
+                .read_to_string(&mut contents)
+                .map_err(|error| format!("Failed to read from standard input: {}", error))?;
+            contents
+        } else if let Some(path) = arg.strip_prefix('@') {
+            std::fs::read_to_string(path).map_err(|error| format!("Failed to read {}: {}", path, error))?
+        } else {
+            items.push(arg.to_owned());
+            continue;
+        };
+
+        for line in contents.lines().filter(|line| !line.trim_start().starts_with('#')) {
+            items.extend(line.split_whitespace().map(String::from));
+        }
+    }
+
+    if items.is_empty() {
+        return Err(String::from("No items were specified."));
+    }
+
+    Ok(items)
+}
+
+/// collection of results for commands that process multiple items
+#[derive(Debug, Default)]
+pub struct Batch {
+    results: Vec<(String, Result<(), String>)>,
+}
+
+impl Batch {
+    pub fn new() -> Self {
+        Batch::default()
+    }
+
+    /// This method returns `true` if no items were processed.
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// This method processes a single item, and records the result.
+    pub async fn run<F>(&mut self, item: &str, process: F)
+    where
+        F: Future<Output = Result<(), String>>,
+    {
+        let result = process.await;
+        self.results.push((item.to_owned(), result));
+    }
+
+    /// This method prints a summary of all results if more than one item was processed, and
+    /// returns an error if processing any item failed. For a single item, its result is returned
+    /// unchanged, and if no items were processed, this is not an error.
+    pub fn finish(mut self) -> Result<(), String> {
+        if self.results.len() <= 1 {
+            return self.results.pop().map_or(Ok(()), |(_, result)| result);
+        }
+
+        let failed = self.results.iter().filter(|(_, result)| result.is_err()).count();
+
+        println!();
+        println!("Summary:");
+        for (item, result) in &self.results {
+            match result {
+                Ok(()) => println!("  ok      {}", item),
+                Err(error) => println!("  failed  {}: {}", item, error),
+            }
+        }
+        println!(
+            "{} of {} items succeeded.",
+            self.results.len() - failed,
+            self.results.len()
+        );
+
+        if failed > 0 {
+            Err(format!("{} of {} items failed.", failed, self.results.len()))
+        } else {
+            Ok(())
+        }
+    }
+}
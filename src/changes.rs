@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use bodhi::*;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{cache_dir, write_atomic};
+use crate::query::{query_update, query_user_overrides, query_user_updates};
+use crate::record::{Recorder, RequestKey};
+use crate::store::server_host;
+
+// updates in these states are tracked; updates that leave them are reported once, and then dropped
+const ACTIVE_STATES: [UpdateStatus; 2] = [UpdateStatus::Pending, UpdateStatus::Testing];
+
+/// last seen state of an update
+#[derive(Debug, Deserialize, Serialize)]
+struct SeenUpdate {
+    status: String,
+    karma: i32,
+    comments: usize,
+}
+
+impl SeenUpdate {
+    fn new(update: &Update) -> Self {
+        SeenUpdate {
+            status: update.status.to_string(),
+            karma: update.karma.unwrap_or_default(),
+            comments: update.comments.as_ref().map_or(0, |comments| comments.len()),
+        }
+    }
+}
+
+/// last seen state of an override
+#[derive(Debug, Deserialize, Serialize)]
+struct SeenOverride {
+    expiration_date: String,
+}
+
+/// state of a user's updates and overrides, as seen by the previous run of the `changes` command
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct SeenState {
+    updates: BTreeMap<String, SeenUpdate>,
+    overrides: BTreeMap<String, SeenOverride>,
+}
+
+/// This function returns the path of the state file for the given user and bodhi instance.
+fn state_path(server_url: &str, username: &str) -> Result<PathBuf, String> {
+    Ok(cache_dir()?.join(format!("changes-{}-{}.json", server_host(server_url), username)))
+}
+
+impl SeenState {
+    /// This method reads the state from the previous run, and returns `None` if there was none.
+    fn load(path: &Path) -> Result<Option<SeenState>, String> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(_) => return Ok(None),
+        };
+
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|_| format!("Failed to parse state file: {}", path.display()))
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|error| format!("Failed to create {}: {}", parent.display(), error))?;
+        }
+
+        let contents = serde_json::to_string_pretty(self).map_err(|error| error.to_string())?;
+
+        write_atomic(path, &contents).map_err(|error| format!("Failed to write state file: {}", error))
+    }
+}
+
+/// This function returns the differences between the last seen and the current state of an update.
+fn update_changes(seen: &SeenUpdate, update: &Update) -> Vec<String> {
+    let mut changes = Vec::new();
+    let current = SeenUpdate::new(update);
+
+    if current.status != seen.status {
+        changes.push(format!("status: {} -> {}", &seen.status, &current.status));
+    }
+
+    if current.karma != seen.karma {
+        changes.push(format!("karma: {} -> {}", seen.karma, current.karma));
+    }
+
+    // comments are returned in chronological order, so new comments are always at the end
+    let comments = update.comments.as_deref().unwrap_or_default();
+    for comment in comments.iter().skip(seen.comments) {
+        changes.push(format!(
+            "new comment by {} ({}): {}",
+            &comment.user.name,
+            comment.karma,
+            comment.text.lines().next().unwrap_or_default()
+        ));
+    }
+
+    changes
+}
+
+fn print_update_changes(heading: &str, changes: &[String]) {
+    println!("{}", heading);
+    for change in changes {
+        println!("  {}", change);
+    }
+}
+
+/// This function reports all changes of the user's updates and overrides since the previous run,
+/// and stores the current state for the next run.
+pub async fn changes(bodhi: &BodhiClient, recorder: &Recorder, server_url: &str, username: &str) -> Result<(), String> {
+    let path = state_path(server_url, username)?;
+    let previous = SeenState::load(&path)?;
+
+    let updates = query_user_updates(bodhi, recorder, username, &ACTIVE_STATES).await?;
+    let overrides = query_user_overrides(bodhi, recorder, username).await?;
+
+    let mut current = SeenState::default();
+    for update in &updates {
+        current.updates.insert(update.alias.clone(), SeenUpdate::new(update));
+    }
+    for over_ride in &overrides {
+        current.overrides.insert(
+            over_ride.nvr.clone(),
+            SeenOverride {
+                expiration_date: over_ride.expiration_date.to_string(),
+            },
+        );
+    }
+
+    let previous = match previous {
+        Some(previous) => previous,
+        None => {
+            current.save(&path)?;
+            println!(
+                "Recorded the state of {} updates and {} overrides of {}.",
+                current.updates.len(),
+                current.overrides.len(),
+                username
+            );
+            println!("Changes will be shown the next time this command is run.");
+            return Ok(());
+        },
+    };
+
+    let mut changed = false;
+
+    for update in &updates {
+        match previous.updates.get(&update.alias) {
+            Some(seen) => {
+                let changes = update_changes(seen, update);
+                if !changes.is_empty() {
+                    print_update_changes(&format!("Update {} ({}):", &update.alias, &update.title), &changes);
+                    changed = true;
+                }
+            },
+            None => {
+                println!("New update {} ({}): {}", &update.alias, &update.title, update.status);
+                changed = true;
+            },
+        }
+    }
+
+    // updates that are no longer active have been pushed to stable, obsoleted, or unpushed
+    for (alias, seen) in &previous.updates {
+        if current.updates.contains_key(alias) {
+            continue;
+        }
+
+        let update = query_update(bodhi, recorder, alias).await?;
+        let changes = update_changes(seen, &update);
+        if !changes.is_empty() {
+            print_update_changes(&format!("Update {} ({}):", &update.alias, &update.title), &changes);
+            changed = true;
+        }
+    }
+
+    for nvr in current.overrides.keys() {
+        if !previous.overrides.contains_key(nvr) {
+            println!("New override for {}", nvr);
+            changed = true;
+        }
+    }
+
+    for (nvr, seen) in &previous.overrides {
+        if current.overrides.contains_key(nvr) {
+            continue;
+        }
+
+        let over_ride: Option<Override> = recorder
+            .optional(
+                &RequestKey::get(format!("/overrides/{}", nvr)),
+                bodhi.request(&OverrideNVRQuery::new(nvr)),
+            )
+            .await?;
+
+        match over_ride.and_then(|over_ride| over_ride.expired_date) {
+            Some(expired_date) => println!("Override for {} expired on {}.", nvr, expired_date),
+            None => println!(
+                "Override for {} is no longer active (it was set to expire on {}).",
+                nvr, &seen.expiration_date
+            ),
+        }
+        changed = true;
+    }
+
+    if !changed {
+        println!("No changes since the last run.");
+    }
+
+    current.save(&path)
+}
@@ -0,0 +1,262 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use bodhi::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::fs;
+
+use crate::config::{cache_dir, write_atomic};
+use crate::output::{progress_finish, progress_pages};
+use crate::record::{Recordable, Recorder, RequestKey};
+
+/// number of results that are requested per page for checkpointed queries
+pub const PAGE_ROWS: u32 = 50;
+
+/// maximum age of checkpoints that can be resumed, since results change on the server over time
+const MAX_CHECKPOINT_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// results of paginated queries, which are identified by a unique key
+///
+/// Pages that were fetched by different runs of a query can overlap, if results were added on the
+/// server in between. This key is used for removing duplicates when combining them.
+pub trait QueryResult {
+    fn result_key(&self) -> String;
+}
+
+impl QueryResult for Build {
+    fn result_key(&self) -> String {
+        self.nvr.clone()
+    }
+}
+
+impl QueryResult for Comment {
+    fn result_key(&self) -> String {
+        self.id.to_string()
+    }
+}
+
+impl QueryResult for Override {
+    fn result_key(&self) -> String {
+        self.nvr.clone()
+    }
+}
+
+impl QueryResult for Package {
+    fn result_key(&self) -> String {
+        self.name.clone()
+    }
+}
+
+impl QueryResult for Update {
+    fn result_key(&self) -> String {
+        self.alias.clone()
+    }
+}
+
+impl QueryResult for User {
+    fn result_key(&self) -> String {
+        self.name.clone()
+    }
+}
+
+/// This function computes a stable (FNV-1a) hash of the given string. It is used for deriving
+/// directory names from query parameters, so it must not change between program runs.
+fn fnv1a(input: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for byte in input.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash
+}
+
+/// This function returns the directory where pages for the query with the given key are stored.
+fn checkpoint_dir(key: &str) -> Result<PathBuf, String> {
+    Ok(cache_dir()?.join("queries").join(format!("{:016x}", fnv1a(key))))
+}
+
+fn page_path(dir: &Path, page: u32) -> PathBuf {
+    dir.join(format!("page-{:05}.json", page))
+}
+
+/// This function reads the page with the given number from the checkpoint directory, if it exists.
+async fn read_page<T>(dir: &Path, page: u32) -> Result<Option<Vec<T>>, String>
+where
+    T: DeserializeOwned,
+{
+    let path = page_path(dir, page);
+
+    let contents = match fs::read_to_string(&path).await {
+        Ok(contents) => contents,
+        Err(_) => return Ok(None),
+    };
+
+    match serde_json::from_str(&contents) {
+        Ok(items) => Ok(Some(items)),
+        Err(_) => Err(format!("Failed to parse checkpoint file: {}", path.display())),
+    }
+}
+
+/// This function writes a page of results to the checkpoint directory.
+fn write_page<T>(dir: &Path, page: u32, items: &[T]) -> Result<(), String>
+where
+    T: Serialize,
+{
+    let contents = serde_json::to_string(items).map_err(|_| String::from("Failed to serialize results."))?;
+
+    write_atomic(&page_path(dir, page), &contents)
+        .map_err(|error| format!("Failed to write checkpoint file: {}", error))
+}
+
+/// This function returns `true` if the checkpoints in the given directory were started more than
+/// [`MAX_CHECKPOINT_AGE`] ago. The query file is only written by the first run of a query, so its
+/// modification time is the time when the first page was fetched.
+async fn expired(dir: &Path) -> bool {
+    let modified = match fs::metadata(dir.join("query.json")).await {
+        Ok(metadata) => metadata.modified(),
+        Err(_) => return false,
+    };
+
+    match modified.map(|modified| SystemTime::now().duration_since(modified)) {
+        Ok(Ok(age)) => age > MAX_CHECKPOINT_AGE,
+        _ => false,
+    }
+}
+
+/// This function runs a paginated query one page at a time, and stores every completed page in a
+/// cache directory that is derived from the query parameters in `key`.
+///
+/// If `resume` is `true`, pages that were already fetched by a previous (interrupted) run of the
+/// same query are loaded from disk instead of being fetched again, unless they are older than
+/// [`MAX_CHECKPOINT_AGE`]. Otherwise, any leftover pages are discarded first. The checkpoint
+/// directory is removed once the query has completed.
+///
+/// The `fetch` function is called with page numbers starting at 1, and needs to request pages with
+/// [`PAGE_ROWS`] results per page. Single page requests do not return the total number of pages, so
+/// a page with fewer results marks the end of the query.
+pub async fn checkpointed_request<T, F, Fut>(
+    key: &str,
+    resume: bool,
+    progress: bool,
+    fetch: F,
+) -> Result<Vec<T>, String>
+where
+    T: Serialize + DeserializeOwned + QueryResult,
+    F: Fn(u32) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, String>>,
+{
+    let dir = checkpoint_dir(key)?;
+
+    let stale = resume && expired(&dir).await;
+    if stale {
+        eprintln!("Checkpoints are older than 24 hours, starting over.");
+    }
+
+    if (!resume || stale) && fs::metadata(&dir).await.is_ok() {
+        fs::remove_dir_all(&dir)
+            .await
+            .map_err(|error| format!("Failed to remove stale checkpoints: {}", error))?;
+    }
+
+    fs::create_dir_all(&dir)
+        .await
+        .map_err(|error| format!("Failed to create checkpoint directory: {}", error))?;
+
+    let query_path = dir.join("query.json");
+    if fs::metadata(&query_path).await.is_err() {
+        fs::write(&query_path, key)
+            .await
+            .map_err(|error| format!("Failed to write checkpoint file: {}", error))?;
+    }
+
+    let mut results: Vec<T> = Vec::new();
+    let mut page = 1u32;
+
+    loop {
+        // pages from earlier runs only exist at this point if the query is being resumed
+        let (items, cached) = match read_page(&dir, page).await? {
+            Some(items) => (items, true),
+            None => (fetch(page).await?, false),
+        };
+
+        if !cached {
+            write_page(&dir, page, &items)?;
+        }
+
+        if progress {
+            progress_pages(page, cached);
+        }
+
+        let last = items.len() < PAGE_ROWS as usize;
+        results.extend(items);
+
+        if last {
+            break;
+        }
+
+        page += 1;
+    }
+
+    if progress {
+        progress_finish();
+    }
+
+    fs::remove_dir_all(&dir)
+        .await
+        .map_err(|error| format!("Failed to remove checkpoint directory: {}", error))?;
+
+    // if pages overlap, the copy from the more recently fetched page is kept
+    let mut seen = HashSet::new();
+    let mut unique: Vec<T> = results
+        .into_iter()
+        .rev()
+        .filter(|result| seen.insert(result.result_key()))
+        .collect();
+    unique.reverse();
+
+    Ok(unique)
+}
+
+/// This function runs a paginated bodhi query with [`checkpointed_request`], and passes every page
+/// request through the recorder.
+///
+/// The `page_query` function returns the page query for a page number, and `fetch` sends it to the
+/// server at `path`. The checkpoint key is derived from `scope` (the server URL, and the command,
+/// if pages should not be shared with other commands) and from the serialized parameters of the
+/// query.
+pub async fn checkpointed_query<T, Q, P, F, Fut>(
+    recorder: &Recorder,
+    scope: &str,
+    path: &str,
+    resume: bool,
+    progress: bool,
+    page_query: P,
+    fetch: F,
+) -> Result<Vec<T>, String>
+where
+    T: Serialize + DeserializeOwned + QueryResult,
+    Vec<T>: Recordable,
+    Q: Serialize,
+    P: Fn(u32) -> Q,
+    F: Fn(Q) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, QueryError>>,
+{
+    let key = format!(
+        "{} {}",
+        scope,
+        serde_json::to_string(&page_query(0)).map_err(|_| String::from("Failed to serialize query."))?
+    );
+
+    let (page_query, fetch) = (&page_query, &fetch);
+    checkpointed_request(&key, resume, progress, |page| async move {
+        recorder
+            .request(&RequestKey::query(path, &page_query(page))?, fetch(page_query(page)))
+            .await
+    })
+    .await
+}
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use bodhi::*;
@@ -29,6 +30,114 @@ impl FromStr for Format {
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+pub enum WatchTarget {
+    Pushed,
+    Stable,
+    Testing,
+}
+
+impl TryFrom<&str> for WatchTarget {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<WatchTarget, String> {
+        match value.to_lowercase().as_str() {
+            "pushed" => Ok(WatchTarget::Pushed),
+            "stable" => Ok(WatchTarget::Stable),
+            "testing" => Ok(WatchTarget::Testing),
+            _ => Err(format!("Not a recognised value for target state: {}", &value)),
+        }
+    }
+}
+
+impl FromStr for WatchTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<WatchTarget, String> {
+        TryFrom::try_from(s)
+    }
+}
+
+impl std::fmt::Display for WatchTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let value = match self {
+            WatchTarget::Pushed => "pushed",
+            WatchTarget::Stable => "stable",
+            WatchTarget::Testing => "testing",
+        };
+
+        write!(f, "{}", value)
+    }
+}
+
+// feedback arguments have the form "ITEM:KARMA", where the item itself may contain colons
+fn split_feedback(value: &str) -> Result<(&str, Karma), String> {
+    let (item, karma) = value
+        .rsplit_once(':')
+        .ok_or_else(|| format!("Not a valid feedback value (expected ITEM:KARMA): {}", value))?;
+
+    let karma = Karma::try_from(karma).map_err(|error| error.to_string())?;
+
+    Ok((item.trim_matches('"'), karma))
+}
+
+#[derive(Clone, Debug)]
+pub struct BugKarma {
+    pub bug_id: u32,
+    pub karma: Karma,
+}
+
+impl TryFrom<&str> for BugKarma {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<BugKarma, String> {
+        let (bug_id, karma) = split_feedback(value)?;
+
+        let bug_id = bug_id.parse().map_err(|_| format!("Not a valid bug ID: {}", bug_id))?;
+
+        Ok(BugKarma { bug_id, karma })
+    }
+}
+
+impl FromStr for BugKarma {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<BugKarma, String> {
+        TryFrom::try_from(s)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TestCaseKarma {
+    pub name: String,
+    pub karma: Karma,
+}
+
+impl TryFrom<&str> for TestCaseKarma {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<TestCaseKarma, String> {
+        let (name, karma) = split_feedback(value)?;
+
+        if name.is_empty() {
+            return Err(format!("Not a valid test case name: {}", value));
+        }
+
+        Ok(TestCaseKarma {
+            name: name.to_owned(),
+            karma,
+        })
+    }
+}
+
+impl FromStr for TestCaseKarma {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<TestCaseKarma, String> {
+        TryFrom::try_from(s)
+    }
+}
+
 /// bodhi-cli expects a configuration file at ~/.config/fedora.toml, with at
 /// least the following contents:
 ///
@@ -62,6 +171,18 @@ pub struct BaseCommand {
     /// Make output more verbose
     #[arg(long, short = 'v')]
     pub verbose: bool,
+    /// Answer queries from the local database instead of the server
+    #[arg(long)]
+    pub offline: bool,
+    /// Record all responses from the server to a directory
+    #[arg(long, value_name = "DIR", conflicts_with("replay"))]
+    pub record: Option<PathBuf>,
+    /// Replay responses from a recording instead of contacting the server
+    #[arg(long, value_name = "DIR")]
+    pub replay: Option<PathBuf>,
+    /// Show requests that would modify data on the server instead of sending them
+    #[arg(long)]
+    pub dry_run: bool,
     #[command(subcommand)]
     pub subcommand: BodhiCommand,
 }
@@ -69,17 +190,70 @@ pub struct BaseCommand {
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Parser)]
 pub enum BodhiCommand {
+    /// Create or edit an update to match a manifest file
+    ///
+    /// The manifest is a TOML file that describes an update, for example:
+    ///
+    /// """
+    /// builds = ["rust-foo-1.0.0-1.fc40"]
+    /// notes = "Update to version 1.0.0."
+    /// bugs = [123456]
+    /// type = "enhancement"
+    /// severity = "low"
+    /// stable_karma = 3
+    /// """
+    ///
+    /// Supported fields are builds, notes, autokarma, autotime, bugs,
+    /// close_bugs, display_name, require_bugs, require_testcases, requirements,
+    /// severity, stable_days, stable_karma, suggestion, unstable_karma, and
+    /// type. If no update exists for the builds yet, it is created. Otherwise,
+    /// the changes are shown, and only fields that differ are changed.
+    #[command(verbatim_doc_comment)]
+    Apply {
+        /// Path of the manifest file
+        manifest: PathBuf,
+    },
+    /// Query bodhi for information about a build
+    BuildInfo {
+        /// NVR of the build
+        nvr: String,
+        /// Output format (plain, JSON)
+        #[arg(long)]
+        format: Option<Format>,
+    },
+    /// Show what changed for your updates and overrides since the last run
+    Changes,
     /// Comment on an update
     Comment {
-        /// ID of the update to comment on
-        #[arg(long)]
-        update: String,
+        /// IDs of the updates to comment on ("-" for standard input, "@FILE" for a file)
+        #[arg(long, required = true, num_args = 1..)]
+        update: Vec<String>,
         /// Publicly visible comment text
-        #[arg(long)]
-        text: String,
+        #[arg(long, required_unless_present_any(["text_file", "edit"]), conflicts_with_all(["text_file", "edit"]))]
+        text: Option<String>,
+        /// Read comment text from a file ("-" for standard input)
+        #[arg(long, value_name = "FILE", conflicts_with("edit"))]
+        text_file: Option<PathBuf>,
+        /// Compose comment text in $EDITOR
+        #[arg(long)]
+        edit: bool,
         /// Karma submitted with this comment (-1/0/+1)
         #[arg(long)]
         karma: Option<Karma>,
+        /// Feedback for a bug associated with the update (e.g. 123456:+1)
+        #[arg(long, value_name = "BUG:KARMA")]
+        bug_feedback: Vec<BugKarma>,
+        /// Feedback for a test case associated with the update (e.g. QA:Testcase_foo:-1)
+        #[arg(long, value_name = "TESTCASE:KARMA")]
+        testcase_feedback: Vec<TestCaseKarma>,
+    },
+    /// Query bodhi for information about a comment
+    CommentInfo {
+        /// numerical ID of the comment
+        id: u32,
+        /// Output format (plain, JSON)
+        #[arg(long)]
+        format: Option<Format>,
     },
     /// Query bodhi for information about a compose
     ComposeInfo {
@@ -97,6 +271,22 @@ pub enum BodhiCommand {
         #[arg(long)]
         format: Option<Format>,
     },
+    /// Wait for a compose to finish
+    ComposeWait {
+        /// release string
+        release: FedoraRelease,
+        /// request string ("stable" or "testing")
+        request: ComposeRequest,
+        /// polling interval (in seconds)
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+        /// stop waiting after this many seconds
+        #[arg(long)]
+        timeout: Option<u64>,
+        /// Send a desktop notification when the compose has finished or failed
+        #[arg(long)]
+        notify: bool,
+    },
     /// Create a new buildroot override
     CreateOverride {
         /// NVR of the override
@@ -110,8 +300,10 @@ pub enum BodhiCommand {
     },
     /// Create buildroot overrides from an update
     CreateUpdateOverride {
-        /// alias of the update (i.e. "FEDORA-2022-XXXXXXXXXX")
-        alias: String,
+        /// aliases of the updates (i.e. "FEDORA-2022-XXXXXXXXXX", "-" for standard input, "@FILE"
+        /// for a file)
+        #[arg(required = true)]
+        aliases: Vec<String>,
         /// duration (in days) it should be active
         #[arg(long)]
         duration: u32,
@@ -143,8 +335,14 @@ pub enum BodhiCommand {
         #[arg(long, conflicts_with = "builds")]
         from_tag: Option<String>,
         /// Publicly visible update notes
+        #[arg(long, required_unless_present_any(["notes_file", "edit"]), conflicts_with_all(["notes_file", "edit"]))]
+        notes: Option<String>,
+        /// Read update notes from a file ("-" for standard input)
+        #[arg(long, value_name = "FILE", conflicts_with("edit"))]
+        notes_file: Option<PathBuf>,
+        /// Compose update notes in $EDITOR
         #[arg(long)]
-        notes: String,
+        edit: bool,
         /// Require bug feedback for karma to count
         #[arg(long)]
         require_bugs: Option<bool>,
@@ -173,16 +371,42 @@ pub enum BodhiCommand {
         #[arg(long, name = "type")]
         update_type: Option<UpdateType>,
     },
+    /// Show an overview of your updates and overrides
+    Dashboard {
+        /// show overrides that expire within this many days
+        #[arg(long, default_value_t = 7)]
+        expiring_days: u32,
+        /// Output format (plain, JSON)
+        #[arg(long)]
+        format: Option<Format>,
+    },
+    /// Interactively submit karma for testing updates of installed packages
+    EasyKarma {
+        /// release of the installed system (i.e. "F40")
+        #[arg(long)]
+        release: FedoraRelease,
+        /// Read installed packages from a file with "rpm -qa --queryformat '%{SOURCERPM}\n'" output
+        #[arg(long, value_name = "FILE", conflicts_with("rpm_command"))]
+        rpm_list: Option<PathBuf>,
+        /// Run this command to list source RPMs of installed packages (default: "rpm -qa" with
+        /// "%{SOURCERPM}")
+        #[arg(long, value_name = "COMMAND")]
+        rpm_command: Option<String>,
+    },
     /// Edit an existing buildroot override
     EditOverride {
-        /// NVR of the override
-        nvr: String,
+        /// NVRs of the overrides ("-" for standard input, "@FILE" for a file)
+        #[arg(required = true)]
+        nvrs: Vec<String>,
         /// duration it will still be active
         #[arg(long)]
         duration: u32,
         /// publicly visible notes
         #[arg(long)]
         notes: String,
+        /// Apply changes without asking for confirmation
+        #[arg(long, short)]
+        yes: bool,
     },
     /// Edit an existing update
     EditUpdate {
@@ -207,8 +431,14 @@ pub enum BodhiCommand {
         #[arg(long)]
         display_name: Option<String>,
         /// Publicly visible update notes
-        #[arg(long)]
+        #[arg(long, conflicts_with_all(["notes_file", "edit"]))]
         notes: Option<String>,
+        /// Read update notes from a file ("-" for standard input)
+        #[arg(long, value_name = "FILE", conflicts_with("edit"))]
+        notes_file: Option<PathBuf>,
+        /// Edit update notes in $EDITOR, starting from the current notes
+        #[arg(long)]
+        edit: bool,
         /// Remove bugs from this update
         #[arg(long)]
         remove_bugs: Option<Vec<u32>>,
@@ -236,11 +466,83 @@ pub enum BodhiCommand {
         /// Type of the update
         #[arg(long, name = "type")]
         update_type: Option<UpdateType>,
+        /// Apply changes without asking for confirmation
+        #[arg(long, short)]
+        yes: bool,
     },
     /// Expire an existing buildroot override
     ExpireOverride {
-        /// NVR of the override
+        /// NVRs of the overrides ("-" for standard input, "@FILE" for a file)
+        #[arg(required = true)]
+        nvrs: Vec<String>,
+    },
+    /// Query bodhi for information about a buildroot override
+    OverrideInfo {
+        /// NVR of the overridden build
         nvr: String,
+        /// Output format (plain, JSON)
+        #[arg(long)]
+        format: Option<Format>,
+    },
+    /// Request stable pushes for all of your updates that are eligible
+    PushEligible {
+        /// Submit requests without asking for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Query bodhi for builds
+    QueryBuilds {
+        /// Query for this build
+        #[arg(long)]
+        nvr: Option<String>,
+        /// Query for builds of these packages
+        #[arg(long)]
+        packages: Option<Vec<String>>,
+        /// Output format (plain, JSON)
+        #[arg(long)]
+        format: Option<Format>,
+        /// Query for this release / these releases
+        #[arg(long)]
+        releases: Option<Vec<FedoraRelease>>,
+        /// Query for builds that are part of these updates
+        #[arg(long)]
+        updates: Option<Vec<String>>,
+        /// Force long-running queries
+        #[arg(long, short)]
+        force: bool,
+        /// Continue an interrupted query from the last completed page
+        #[arg(long)]
+        resume: bool,
+    },
+    /// Query bodhi for comments
+    QueryComments {
+        /// Output format (plain, JSON)
+        #[arg(long)]
+        format: Option<Format>,
+        /// Ignore comments submitted by these users
+        #[arg(long)]
+        ignore_users: Option<Vec<String>>,
+        /// Query for comments on updates for these packages
+        #[arg(long)]
+        packages: Option<Vec<String>>,
+        /// Query for comments submitted after this date
+        #[arg(long)]
+        since: Option<BodhiDate>,
+        /// Query for comments on updates submitted by these users
+        #[arg(long)]
+        update_owners: Option<Vec<String>>,
+        /// Query for comments on these updates
+        #[arg(long)]
+        updates: Option<Vec<String>>,
+        /// Query for comments submitted by these users
+        #[arg(long)]
+        users: Option<Vec<String>>,
+        /// Force long-running queries
+        #[arg(long, short)]
+        force: bool,
+        /// Continue an interrupted query from the last completed page
+        #[arg(long)]
+        resume: bool,
     },
     /// Query bodhi for buildroot overrides
     QueryOverrides {
@@ -262,6 +564,30 @@ pub enum BodhiCommand {
         /// Force long-running queries
         #[arg(long, short)]
         force: bool,
+        /// Continue an interrupted query from the last completed page
+        #[arg(long)]
+        resume: bool,
+    },
+    /// Query bodhi for packages
+    QueryPackages {
+        /// Output format (plain, JSON)
+        #[arg(long)]
+        format: Option<Format>,
+        /// Query for packages with names that contain this string
+        #[arg(long)]
+        like: Option<String>,
+        /// Query for the package with this name
+        #[arg(long)]
+        name: Option<String>,
+        /// Query for packages with names that contain this string (case-insensitive)
+        #[arg(long)]
+        search: Option<String>,
+        /// Force long-running queries
+        #[arg(long, short)]
+        force: bool,
+        /// Continue an interrupted query from the last completed page
+        #[arg(long)]
+        resume: bool,
     },
     /// Query bodhi for updates
     QueryUpdates {
@@ -283,6 +609,9 @@ pub enum BodhiCommand {
         /// Output format (plain, JSON)
         #[arg(long)]
         format: Option<Format>,
+        /// Only print these fields, separated by tabs (e.g. "alias,release.name")
+        #[arg(long, value_name = "FIELD,...", value_delimiter = ',', conflicts_with("format"))]
+        fields: Option<Vec<String>>,
         /// locked updates
         #[arg(long)]
         locked: Option<bool>,
@@ -334,6 +663,30 @@ pub enum BodhiCommand {
         /// Force long-running queries
         #[arg(long, short)]
         force: bool,
+        /// Continue an interrupted query from the last completed page
+        #[arg(long)]
+        resume: bool,
+    },
+    /// Query bodhi for users
+    QueryUsers {
+        /// Output format (plain, JSON)
+        #[arg(long)]
+        format: Option<Format>,
+        /// Query for members of these groups
+        #[arg(long)]
+        groups: Option<Vec<String>>,
+        /// Query for users with names that contain this string
+        #[arg(long)]
+        like: Option<String>,
+        /// Query for the user with this name
+        #[arg(long)]
+        name: Option<String>,
+        /// Force long-running queries
+        #[arg(long, short)]
+        force: bool,
+        /// Continue an interrupted query from the last completed page
+        #[arg(long)]
+        resume: bool,
     },
     /// Query bodhi for information about a release
     ReleaseInfo {
@@ -349,45 +702,169 @@ pub enum BodhiCommand {
         #[arg(long)]
         format: Option<Format>,
     },
-    /// Submit an update status request
-    UpdateRequest {
+    /// Run a read-only SQL query against the local database
+    ///
+    /// The local database is populated by the "sync" subcommand. It contains these tables:
+    ///
+    /// - updates (alias, update_id, title, release, status, request, update_type, severity,
+    ///   submitter, karma, date_submitted, date_modified, date_pushed, date_stable, data)
+    /// - builds (nvr, update_alias, build_type, signed)
+    /// - bugs (bug_id, update_alias, title, security)
+    /// - comments (id, update_id, update_alias, user, karma, timestamp, text, data)
+    /// - overrides (nvr, submitter, notes, submission_date, expiration_date, expired_date, data)
+    /// - releases (name, long_name, state, branch, data)
+    /// - composes (release, request, state, date_created, data)
+    ///
+    /// And these views:
+    ///
+    /// - active_overrides (nvr, submitter, notes, submission_date, expiration_date)
+    /// - update_karma (alias, release, status, karma, stable_karma, unstable_karma, positive,
+    ///   negative)
+    /// - bug_feedback (comment_id, update_alias, user, bug_id, karma)
+    /// - testcase_feedback (comment_id, update_alias, user, testcase, karma)
+    ///
+    /// The "data" columns contain the full JSON representation of the stored objects, which can
+    /// be accessed with SQLite JSON functions like json_extract(data, '$.notes').
+    #[command(verbatim_doc_comment)]
+    Sql {
+        /// SQL query (only the first statement is run)
+        query: String,
+        /// Output format (plain, JSON)
+        #[arg(long)]
+        format: Option<Format>,
+    },
+    /// Synchronize the local database with data from bodhi
+    Sync {
+        /// Only mirror updates and overrides for these releases
+        #[arg(long)]
+        releases: Option<Vec<FedoraRelease>>,
+        /// Fetch all data again instead of only changes since the last run
+        #[arg(long)]
+        full: bool,
+        /// Continue an interrupted full synchronization from the last completed page
+        #[arg(long)]
+        resume: bool,
+    },
+    /// Query bodhi for detailed information about an update
+    UpdateInfo {
         /// ID of the update
         alias: String,
+        /// Output format (plain, JSON)
+        #[arg(long)]
+        format: Option<Format>,
+    },
+    /// Submit an update status request
+    UpdateRequest {
+        /// IDs of the updates ("-" for standard input, "@FILE" for a file)
+        #[arg(required = true)]
+        aliases: Vec<String>,
         /// (obsolete, revoke, stable, testing, unpush)
         request: UpdateRequest,
     },
+    /// Query bodhi for information about a user
+    UserInfo {
+        /// FAS username of the user
+        name: String,
+        /// Output format (plain, JSON)
+        #[arg(long)]
+        format: Option<Format>,
+    },
     /// Waive an update's test results
     WaiveTests {
-        /// ID of the update
-        alias: String,
+        /// IDs of the updates ("-" for standard input, "@FILE" for a file)
+        #[arg(required = true)]
+        aliases: Vec<String>,
         /// comment submitted with the waiver
         comment: String,
         /// test results to be waived (default: empty / all)
         #[arg(long)]
         tests: Option<Vec<String>>,
     },
+    /// Follow an update until it reaches a target state
+    Watch {
+        /// ID of the update
+        alias: String,
+        /// state to wait for (pushed, stable, testing)
+        #[arg(long)]
+        until: WatchTarget,
+        /// initial polling interval (in seconds)
+        #[arg(long, default_value_t = 30)]
+        interval: u64,
+        /// maximum polling interval when nothing changes (in seconds)
+        #[arg(long, default_value_t = 600)]
+        max_interval: u64,
+        /// stop waiting after this many seconds
+        #[arg(long)]
+        timeout: Option<u64>,
+        /// Send desktop notifications for status changes, negative karma, and pushes to stable
+        #[arg(long)]
+        notify: bool,
+    },
 }
 
+const FEDORA_BODHI_URL: &str = "https://bodhi.fedoraproject.org";
+const FEDORA_BODHI_STG_URL: &str = "https://bodhi.stg.fedoraproject.org";
+
 impl BaseCommand {
+    pub fn server_url(&self) -> &str {
+        match (&self.staging, &self.bodhi_url) {
+            (false, None) => FEDORA_BODHI_URL,
+            (true, None) => FEDORA_BODHI_STG_URL,
+            (_, Some(url)) => url.as_str(),
+        }
+    }
+
     pub fn authenticated(&self) -> bool {
         use BodhiCommand::*;
 
         match self.subcommand {
+            Apply { .. } => true,
+            BuildInfo { .. } => false,
+            Changes => false,
             Comment { .. } => true,
+            CommentInfo { .. } => false,
             ComposeInfo { .. } => false,
             ComposeList { .. } => false,
+            ComposeWait { .. } => false,
             CreateOverride { .. } => true,
             CreateUpdateOverride { .. } => true,
             CreateUpdate { .. } => true,
+            Dashboard { .. } => false,
+            EasyKarma { .. } => true,
             EditOverride { .. } => true,
             EditUpdate { .. } => true,
             ExpireOverride { .. } => true,
+            OverrideInfo { .. } => false,
+            PushEligible { .. } => true,
+            QueryBuilds { .. } => false,
+            QueryComments { .. } => false,
             QueryOverrides { .. } => false,
+            QueryPackages { .. } => false,
             QueryUpdates { .. } => false,
+            QueryUsers { .. } => false,
             ReleaseInfo { .. } => false,
             ReleaseList { .. } => false,
+            Sql { .. } => false,
+            Sync { .. } => false,
+            UpdateInfo { .. } => false,
             UpdateRequest { .. } => true,
+            UserInfo { .. } => false,
             WaiveTests { .. } => true,
+            Watch { .. } => false,
         }
     }
+
+    pub fn offline_capable(&self) -> bool {
+        use BodhiCommand::*;
+
+        matches!(
+            self.subcommand,
+            ComposeList { .. }
+                | QueryOverrides { .. }
+                | QueryUpdates { .. }
+                | ReleaseInfo { .. }
+                | ReleaseList { .. }
+                | Sql { .. }
+        )
+    }
 }
@@ -0,0 +1,87 @@
+use bodhi::*;
+
+use crate::cli::{BugKarma, TestCaseKarma};
+use crate::dry_run::DryRun;
+use crate::output::print_server_msgs;
+use crate::record::Recorder;
+
+/// karma and feedback that is submitted together with a comment
+#[derive(Debug, Default)]
+pub struct Feedback {
+    pub karma: Option<Karma>,
+    pub bugs: Vec<BugKarma>,
+    pub test_cases: Vec<TestCaseKarma>,
+}
+
+/// This function posts a comment (with optional karma and feedback) on an update. Feedback is
+/// checked against the bugs and test cases that are associated with the update before anything
+/// is sent to the server.
+pub async fn post_comment(
+    bodhi: &BodhiClient,
+    recorder: &Recorder,
+    dry_run: Option<&DryRun>,
+    update: &Update,
+    text: &str,
+    feedback: &Feedback,
+) -> Result<(), String> {
+    for bug_feedback in &feedback.bugs {
+        if !update.bugs.iter().any(|bug| bug.bug_id == bug_feedback.bug_id) {
+            return Err(format!(
+                "Bug {} is not associated with update {}.",
+                bug_feedback.bug_id, &update.alias
+            ));
+        }
+    }
+
+    let test_cases = update.test_cases.as_deref().unwrap_or_default();
+    for testcase_feedback in &feedback.test_cases {
+        if !test_cases
+            .iter()
+            .any(|test_case| test_case.name == testcase_feedback.name)
+        {
+            return Err(format!(
+                "Test case {} is not associated with update {}.",
+                &testcase_feedback.name, &update.alias
+            ));
+        }
+    }
+
+    let bug_data: Vec<BugFeedbackData> = feedback
+        .bugs
+        .iter()
+        .map(|feedback| BugFeedbackData::new(feedback.bug_id, feedback.karma))
+        .collect();
+    let testcase_data: Vec<TestCaseFeedbackData> = feedback
+        .test_cases
+        .iter()
+        .map(|feedback| TestCaseFeedbackData::new(&feedback.name, feedback.karma))
+        .collect();
+
+    let mut commenter = update.comment().text(text);
+
+    if let Some(karma) = feedback.karma {
+        commenter = commenter.karma(karma);
+    }
+
+    if !bug_data.is_empty() {
+        commenter = commenter.bug_feedback(&bug_data);
+    }
+
+    if !testcase_data.is_empty() {
+        commenter = commenter.testcase_feedback(&testcase_data);
+    }
+
+    if let Some(dry_run) = dry_run {
+        return dry_run.show(dry_run.client().request(&commenter)).await;
+    }
+
+    let comment: NewComment = recorder
+        .post(|client| client.request(&commenter), bodhi.request(&commenter))
+        .await?;
+
+    println!("Comment created.");
+    print_server_msgs(&comment.caveats);
+    println!("{}", &comment.comment);
+
+    Ok(())
+}
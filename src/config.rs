@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use serde::Deserialize;
 use tokio::fs::read_to_string;
 
@@ -42,3 +44,22 @@ pub async fn get_config() -> Result<FedoraConfig, String> {
 
     Ok(config)
 }
+
+/// This function returns the directory where bodhi-cli stores cached data (the local database,
+/// query checkpoints, and the state of the `changes` command).
+pub fn cache_dir() -> Result<PathBuf, String> {
+    match dirs::cache_dir() {
+        Some(path) => Ok(path.join("bodhi-cli")),
+        None => Err(String::from("Unable to determine cache directory.")),
+    }
+}
+
+/// This function writes a file by writing a temporary file next to it first, and then renaming it,
+/// so interrupting the program never leaves a partially written file behind.
+pub fn write_atomic(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+
+    std::fs::write(&temp, contents)?;
+    std::fs::rename(&temp, path)
+}
@@ -0,0 +1,84 @@
+use std::fmt::{Display, Formatter};
+use std::io::{IsTerminal, Write};
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+/// single field that differs between the current and the new state of an update or override
+#[derive(Debug)]
+pub struct Change {
+    pub field: &'static str,
+    pub old: Option<String>,
+    pub new: String,
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match &self.old {
+            Some(old) => write!(f, "~ {}: {} -> {}", self.field, old, &self.new),
+            None => write!(f, "+ {}: {}", self.field, &self.new),
+        }
+    }
+}
+
+impl Change {
+    /// This method formats the change with old values in red and new values in green.
+    fn colored(&self) -> String {
+        match &self.old {
+            Some(old) => format!(
+                "~ {}: {}{}{} -> {}{}{}",
+                self.field, RED, old, RESET, GREEN, &self.new, RESET
+            ),
+            None => format!("+ {}: {}{}{}", self.field, GREEN, &self.new, RESET),
+        }
+    }
+}
+
+/// This function prints a list of changes, with colors if standard output is a terminal.
+pub fn print_changes(changes: &[Change]) {
+    let color = std::io::stdout().is_terminal();
+
+    for change in changes {
+        if color {
+            println!("{}", change.colored());
+        } else {
+            println!("{}", change);
+        }
+    }
+}
+
+/// This function prints a prompt and reads a line of input from the user. It returns `None` if
+/// there is no more input (for example, if standard input is closed).
+pub fn ask(prompt: &str) -> Result<Option<String>, String> {
+    print!("{} ", prompt);
+    std::io::stdout().flush().map_err(|error| error.to_string())?;
+
+    let mut answer = String::new();
+    let read = std::io::stdin()
+        .read_line(&mut answer)
+        .map_err(|error| format!("Failed to read from standard input: {}", error))?;
+
+    if read == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(answer.trim().to_owned()))
+}
+
+/// This function asks the user for confirmation, and fails unless they answered "yes". Without an
+/// interactive terminal, nobody could answer, so it fails without asking.
+pub fn confirm(prompt: &str) -> Result<(), String> {
+    if !std::io::stdin().is_terminal() {
+        return Err(String::from(
+            "Standard input is not a terminal, use the '--yes' flag to skip the confirmation.",
+        ));
+    }
+
+    let answer = ask(&format!("{} [y/N]", prompt))?.unwrap_or_default();
+
+    match answer.to_lowercase().as_str() {
+        "y" | "yes" => Ok(()),
+        _ => Err(String::from("Aborted, use the '--yes' flag to skip the confirmation.")),
+    }
+}
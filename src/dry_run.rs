@@ -0,0 +1,170 @@
+use std::future::Future;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+use bodhi::*;
+
+use crate::record::RequestKey;
+
+// placeholder that is used instead of a real CSRF token, which is only issued to logged-in users
+const DRY_RUN_TOKEN: &str = "(dry run)";
+
+/// request that would have been sent to the server
+#[derive(Debug)]
+struct Captured {
+    method: String,
+    path: String,
+    body: String,
+}
+
+/// helper for running mutating requests in "dry run" mode
+///
+/// Requests are sent to a [`BodhiClient`] that is connected to a minimal HTTP server on localhost
+/// instead of the real bodhi server. That server captures the request, and answers with a "not
+/// found" error. This way, the request payload is constructed by the bodhi crate exactly as it
+/// would be sent to the real server, but nothing is ever sent, and no login is required.
+pub struct DryRun {
+    client: BodhiClient,
+    server_url: String,
+    captured: Arc<Mutex<Vec<Captured>>>,
+}
+
+fn handle_connection(stream: TcpStream, captured: &Mutex<Vec<Captured>>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_owned();
+    let path = parts.next().unwrap_or_default().to_owned();
+
+    let mut length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().unwrap_or_default();
+            }
+        }
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    let (status, response) = if method == "GET" && path.starts_with("/csrf") {
+        ("200 OK", format!("{{\"csrf_token\": \"{}\"}}", DRY_RUN_TOKEN))
+    } else {
+        if let Ok(mut captured) = captured.lock() {
+            captured.push(Captured {
+                method,
+                path,
+                body: String::from_utf8_lossy(&body).into_owned(),
+            });
+        }
+        ("404 Not Found", String::from("{}"))
+    };
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        response.len(),
+        response
+    )?;
+    stream.flush()
+}
+
+impl DryRun {
+    pub async fn start(server_url: &str) -> Result<Self, String> {
+        let listener =
+            TcpListener::bind("127.0.0.1:0").map_err(|error| format!("Failed to start dry run server: {}", error))?;
+        let address = listener.local_addr().map_err(|error| error.to_string())?;
+
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let server_captured = captured.clone();
+
+        // the server thread is never joined, it stops when the process exits
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = handle_connection(stream, &server_captured);
+            }
+        });
+
+        let url = format!("http://{}", address);
+        let client = BodhiClientBuilder::custom(url.clone(), url)
+            .build()
+            .await
+            .map_err(|error| error.to_string())?;
+
+        Ok(DryRun {
+            client,
+            server_url: server_url.trim_end_matches('/').to_owned(),
+            captured,
+        })
+    }
+
+    /// This method returns the client that must be used for making requests in dry run mode.
+    pub fn client(&self) -> &BodhiClient {
+        &self.client
+    }
+
+    // This method runs a request that was made with [`DryRun::client`], and returns the requests
+    // that would have been sent to the server.
+    async fn capture<T, F>(&self, response: F) -> Result<Vec<Captured>, String>
+    where
+        F: Future<Output = Result<T, QueryError>>,
+    {
+        // the response is always an error, because the request is never sent to the real server
+        let _ = response.await;
+
+        let captured: Vec<Captured> = match self.captured.lock() {
+            Ok(mut captured) => captured.drain(..).collect(),
+            Err(_) => return Err(String::from("Failed to capture request.")),
+        };
+
+        if captured.is_empty() {
+            return Err(String::from("Failed to capture request."));
+        }
+
+        Ok(captured)
+    }
+
+    /// This method runs a request that was made with [`DryRun::client`], and returns the key that
+    /// identifies it in recordings, based on the payload that would have been sent to the server.
+    pub async fn request_key<T, F>(&self, response: F) -> Result<RequestKey, String>
+    where
+        F: Future<Output = Result<T, QueryError>>,
+    {
+        match self.capture(response).await?.as_slice() {
+            [request] => Ok(RequestKey::post(&request.method, &request.path, &request.body)),
+            _ => Err(String::from("Failed to capture request.")),
+        }
+    }
+
+    /// This method runs a request that was made with [`DryRun::client`], and prints the endpoint
+    /// and the payload that would have been sent to the server.
+    pub async fn show<T, F>(&self, response: F) -> Result<(), String>
+    where
+        F: Future<Output = Result<T, QueryError>>,
+    {
+        for request in self.capture(response).await? {
+            println!("Dry run, not sending request:");
+            println!("{} {}{}", &request.method, &self.server_url, &request.path);
+
+            match serde_json::from_str::<serde_json::Value>(&request.body) {
+                Ok(value) => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&value).map_err(|error| error.to_string())?
+                ),
+                Err(_) => println!("{}", &request.body),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,520 @@
+use std::fmt::{Display, Formatter};
+
+use bodhi::*;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+
+/// information about a build, combined from the build itself, the update it is part of, and its
+/// buildroot override (if any)
+#[derive(Debug, Serialize)]
+pub struct BuildInfo {
+    pub build: Build,
+    pub release: Option<FedoraRelease>,
+    pub update: Option<Update>,
+    #[serde(rename = "override")]
+    pub over_ride: Option<Override>,
+}
+
+impl Display for BuildInfo {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "Build {}", &self.build.nvr)?;
+        writeln!(f, "Type:     {}", self.build.build_type)?;
+        writeln!(
+            f,
+            "Epoch:    {}",
+            match self.build.epoch {
+                Some(epoch) => epoch.to_string(),
+                None => String::from("(None)"),
+            }
+        )?;
+        writeln!(f, "Signed:   {}", if self.build.signed { "yes" } else { "no" })?;
+
+        match (&self.release, self.build.release_id) {
+            (Some(release), _) => writeln!(f, "Release:  {}", release)?,
+            (None, Some(id)) => writeln!(f, "Release:  (ID {})", id)?,
+            (None, None) => writeln!(f, "Release:  (None)")?,
+        }
+
+        match &self.update {
+            Some(update) => writeln!(f, "Update:   {} ({})", &update.alias, update.status)?,
+            None => writeln!(f, "Update:   (None)")?,
+        }
+
+        match &self.over_ride {
+            Some(over_ride) => match &over_ride.expired_date {
+                None => writeln!(
+                    f,
+                    "Override: active, expires {} (submitted by {})",
+                    &over_ride.expiration_date, &over_ride.submitter.name
+                )?,
+                Some(date) => writeln!(f, "Override: expired {}", date)?,
+            },
+            None => writeln!(f, "Override: (None)")?,
+        }
+
+        Ok(())
+    }
+}
+
+/// This function converts a date as returned by bodhi into a UTC timestamp.
+pub fn parse_date(date: &BodhiDate) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(&date.to_string(), BODHI_DATETIME_FORMAT)
+        .ok()
+        .map(|date| date.and_utc())
+}
+
+/// This function returns why an update in testing can be requested for stable (its karma or the
+/// time it spent in testing reached the threshold), or `None` if it can not be requested yet.
+///
+/// The server has more rules than can be checked here (for example, for critical path updates),
+/// so updates also need to meet the testing requirements as reported by the server, and must not
+/// be blocked by test gating.
+pub fn stable_reason(update: &Update, now: DateTime<Utc>) -> Option<String> {
+    if !matches!(update.status, UpdateStatus::Testing) || update.request.is_some() {
+        return None;
+    }
+
+    if !update.meets_testing_requirements || !gating_passed(update) {
+        return None;
+    }
+
+    let karma = update.karma.unwrap_or_default();
+    if let Some(stable_karma) = update.stable_karma.filter(|stable_karma| *stable_karma > 0) {
+        if karma >= stable_karma {
+            return Some(format!("karma {} reached {}", karma, stable_karma));
+        }
+    }
+
+    if let (Some(stable_days), Some(date_testing)) = (
+        update.stable_days.filter(|stable_days| *stable_days > 0),
+        update.date_testing.as_ref().and_then(parse_date),
+    ) {
+        let days = (now - date_testing).num_days();
+        if days >= stable_days as i64 {
+            return Some(format!("{} days in testing reached {}", days, stable_days));
+        }
+    }
+
+    None
+}
+
+/// This function checks whether test gating does not block an update (tests passed, were waived,
+/// or are not required).
+fn gating_passed(update: &Update) -> bool {
+    matches!(
+        update.test_gating_status,
+        None | Some(TestGatingStatus::Passed) | Some(TestGatingStatus::Ignored)
+    )
+}
+
+/// detailed information about an update, including feedback and the full comment thread
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct UpdateInfo {
+    pub update: Update,
+}
+
+// latest non-neutral feedback from every user, in the order in which users first gave feedback
+fn latest_feedback<'a>(feedback: impl Iterator<Item = (&'a str, Karma)>) -> Vec<(&'a str, Karma)> {
+    let mut latest: Vec<(&str, Karma)> = Vec::new();
+
+    for (user, karma) in feedback.filter(|(_, karma)| *karma != Karma::Neutral) {
+        match latest.iter_mut().find(|(name, _)| *name == user) {
+            Some(entry) => entry.1 = karma,
+            None => latest.push((user, karma)),
+        }
+    }
+
+    latest
+}
+
+fn format_feedback(feedback: &[(&str, Karma)]) -> String {
+    if feedback.is_empty() {
+        return String::from("no feedback");
+    }
+
+    feedback
+        .iter()
+        .map(|(user, karma)| format!("{} ({})", karma, user))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn format_date(date: &Option<BodhiDate>) -> String {
+    match date {
+        Some(date) => date.to_string(),
+        None => String::from("(None)"),
+    }
+}
+
+impl Display for UpdateInfo {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let update = &self.update;
+        let comments: &[Comment] = update.comments.as_deref().unwrap_or_default();
+
+        let votes = latest_feedback(comments.iter().map(|c| (c.user.name.as_str(), c.karma)));
+        let positive = votes.iter().filter(|(_, karma)| *karma == Karma::Positive).count();
+        let negative = votes.iter().filter(|(_, karma)| *karma == Karma::Negative).count();
+
+        writeln!(f, "Update {} ({})", &update.alias, &update.title)?;
+        writeln!(
+            f,
+            "Type:         {} (severity: {})",
+            update.update_type, update.severity
+        )?;
+        writeln!(f, "Release:      {}", &update.release.name)?;
+        match update.request {
+            Some(request) => writeln!(f, "Status:       {} (requested: {})", update.status, request)?,
+            None => writeln!(f, "Status:       {}", update.status)?,
+        }
+        writeln!(f, "Submitter:    {}", &update.user.name)?;
+        writeln!(f, "Critical:     {}", if update.critpath { "yes" } else { "no" })?;
+        writeln!(f)?;
+
+        writeln!(
+            f,
+            "Karma:        {} (+{} / -{})",
+            update.karma.unwrap_or_default(),
+            positive,
+            negative
+        )?;
+        writeln!(
+            f,
+            "Thresholds:   stable at {}, unstable at {}",
+            update.stable_karma.map_or(String::from("(None)"), |k| k.to_string()),
+            update.unstable_karma.map_or(String::from("(None)"), |k| k.to_string()),
+        )?;
+        writeln!(
+            f,
+            "Automatic:    karma {}, time {}",
+            if update.autokarma { "yes" } else { "no" },
+            if update.autotime { "yes" } else { "no" },
+        )?;
+        writeln!(
+            f,
+            "Gating:       {}",
+            match &update.test_gating_status {
+                Some(status) => status.to_string(),
+                None => String::from("(None)"),
+            }
+        )?;
+        writeln!(
+            f,
+            "Requirements: {} ({})",
+            match update.requirements.as_deref() {
+                Some(requirements) if !requirements.is_empty() => requirements,
+                _ => "(None)",
+            },
+            if update.meets_testing_requirements {
+                "met"
+            } else {
+                "not met"
+            }
+        )?;
+        writeln!(f)?;
+
+        writeln!(f, "Submitted:    {}", format_date(&update.date_submitted))?;
+        writeln!(f, "Modified:     {}", format_date(&update.date_modified))?;
+        writeln!(f, "Testing:      {}", format_date(&update.date_testing))?;
+        writeln!(f, "Stable:       {}", format_date(&update.date_stable))?;
+        writeln!(f, "Pushed:       {}", format_date(&update.date_pushed))?;
+        writeln!(f)?;
+
+        writeln!(f, "Notes:")?;
+        writeln!(f, "{}", &update.notes)?;
+        writeln!(f)?;
+
+        writeln!(f, "Builds:")?;
+        for build in &update.builds {
+            writeln!(f, "  {}", &build.nvr)?;
+        }
+        if update.builds.is_empty() {
+            writeln!(f, "  (None)")?;
+        }
+        writeln!(f)?;
+
+        writeln!(f, "Bugs:")?;
+        for bug in &update.bugs {
+            let feedback = latest_feedback(comments.iter().flat_map(|comment| {
+                comment
+                    .bug_feedback
+                    .iter()
+                    .filter(|feedback| feedback.bug_id == bug.bug_id)
+                    .map(|feedback| (comment.user.name.as_str(), feedback.karma))
+            }));
+
+            writeln!(
+                f,
+                "  {}: {} [{}]",
+                bug.bug_id,
+                bug.title.as_deref().unwrap_or("(no title)"),
+                format_feedback(&feedback)
+            )?;
+        }
+        if update.bugs.is_empty() {
+            writeln!(f, "  (None)")?;
+        }
+        writeln!(f)?;
+
+        let test_cases: &[TestCase] = update.test_cases.as_deref().unwrap_or_default();
+
+        writeln!(f, "Test Cases:")?;
+        for test_case in test_cases {
+            let feedback = latest_feedback(comments.iter().flat_map(|comment| {
+                comment
+                    .testcase_feedback
+                    .iter()
+                    .filter(|feedback| feedback.testcase.name == test_case.name)
+                    .map(|feedback| (comment.user.name.as_str(), feedback.karma))
+            }));
+
+            writeln!(f, "  {} [{}]", &test_case.name, format_feedback(&feedback))?;
+        }
+        if test_cases.is_empty() {
+            writeln!(f, "  (None)")?;
+        }
+        writeln!(f)?;
+
+        writeln!(f, "Comments:")?;
+        for comment in comments {
+            writeln!(
+                f,
+                "  {} ({}, karma {}):",
+                &comment.user.name, &comment.timestamp, comment.karma
+            )?;
+            for line in comment.text.lines() {
+                writeln!(f, "    {}", line)?;
+            }
+        }
+        if comments.is_empty() {
+            writeln!(f, "  (None)")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// information about a buildroot override, including its remaining lifetime at the time of the
+/// query
+#[derive(Debug, Serialize)]
+pub struct OverrideInfo {
+    #[serde(flatten)]
+    pub over_ride: Override,
+    pub expired: bool,
+    pub remaining_seconds: Option<i64>,
+}
+
+impl OverrideInfo {
+    pub fn new(over_ride: Override, now: DateTime<Utc>) -> Self {
+        let remaining = match over_ride.expired_date {
+            Some(_) => None,
+            None => parse_date(&over_ride.expiration_date).map(|date| (date - now).num_seconds().max(0)),
+        };
+
+        OverrideInfo {
+            expired: over_ride.expired_date.is_some(),
+            remaining_seconds: remaining,
+            over_ride,
+        }
+    }
+}
+
+impl Display for OverrideInfo {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let over_ride = &self.over_ride;
+
+        writeln!(f, "Override for {}", &over_ride.nvr)?;
+        writeln!(f, "Submitter: {}", &over_ride.submitter.name)?;
+        writeln!(f, "Submitted: {}", &over_ride.submission_date)?;
+        writeln!(f, "Expires:   {}", &over_ride.expiration_date)?;
+
+        match (&over_ride.expired_date, self.remaining_seconds) {
+            (Some(date), _) => writeln!(f, "State:     expired {}", date)?,
+            (None, Some(seconds)) => writeln!(
+                f,
+                "State:     active, {} days and {} hours remaining",
+                seconds / 86400,
+                (seconds % 86400) / 3600
+            )?,
+            (None, None) => writeln!(f, "State:     active")?,
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Notes:")?;
+        writeln!(f, "{}", &over_ride.notes)?;
+
+        Ok(())
+    }
+}
+
+/// summary of an update, as shown on the dashboard
+#[derive(Debug, Serialize)]
+pub struct DashboardUpdate {
+    pub alias: String,
+    pub title: String,
+    pub status: UpdateStatus,
+    pub karma: i32,
+    pub test_gating_status: Option<TestGatingStatus>,
+    pub reason: Option<String>,
+}
+
+impl DashboardUpdate {
+    fn new(update: &Update, reason: Option<String>) -> Self {
+        DashboardUpdate {
+            alias: update.alias.clone(),
+            title: update.title.clone(),
+            status: update.status,
+            karma: update.karma.unwrap_or_default(),
+            test_gating_status: update.test_gating_status,
+            reason,
+        }
+    }
+}
+
+impl Display for DashboardUpdate {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{} ({}), karma {}", &self.alias, &self.title, self.karma)?;
+
+        if let Some(gating) = &self.test_gating_status {
+            write!(f, ", gating {}", gating)?;
+        }
+
+        if let Some(reason) = &self.reason {
+            write!(f, ": {}", reason)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// overview of a user's updates and buildroot overrides
+#[derive(Debug, Serialize)]
+pub struct Dashboard {
+    pub user: String,
+    pub pending: Vec<DashboardUpdate>,
+    pub testing: Vec<DashboardUpdate>,
+    pub eligible: Vec<DashboardUpdate>,
+    pub attention: Vec<DashboardUpdate>,
+    pub expiring_days: u32,
+    pub expiring_overrides: Vec<OverrideInfo>,
+}
+
+impl Dashboard {
+    pub fn new(
+        user: &str,
+        updates: &[Update],
+        overrides: Vec<Override>,
+        expiring_days: u32,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut pending = Vec::new();
+        let mut testing = Vec::new();
+        let mut eligible = Vec::new();
+        let mut attention = Vec::new();
+
+        for update in updates {
+            match update.status {
+                UpdateStatus::Pending => pending.push(DashboardUpdate::new(update, None)),
+                UpdateStatus::Testing => testing.push(DashboardUpdate::new(update, None)),
+                _ => continue,
+            }
+
+            if let Some(reason) = stable_reason(update, now) {
+                eligible.push(DashboardUpdate::new(update, Some(reason)));
+            }
+
+            let comments: &[Comment] = update.comments.as_deref().unwrap_or_default();
+            let votes = latest_feedback(comments.iter().map(|c| (c.user.name.as_str(), c.karma)));
+            let negative: Vec<&str> = votes
+                .iter()
+                .filter(|(_, karma)| *karma == Karma::Negative)
+                .map(|(user, _)| *user)
+                .collect();
+
+            let mut problems = Vec::new();
+            if !negative.is_empty() {
+                problems.push(format!("negative karma from {}", negative.join(", ")));
+            }
+            if matches!(
+                update.test_gating_status,
+                Some(TestGatingStatus::Failed) | Some(TestGatingStatus::GreenwaveFailed)
+            ) {
+                problems.push(String::from("failed gating"));
+            }
+
+            if !problems.is_empty() {
+                attention.push(DashboardUpdate::new(update, Some(problems.join(", "))));
+            }
+        }
+
+        let expiring_overrides = overrides
+            .into_iter()
+            .map(|over_ride| OverrideInfo::new(over_ride, now))
+            .filter(|info| {
+                info.remaining_seconds
+                    .is_some_and(|seconds| seconds <= expiring_days as i64 * 86400)
+            })
+            .collect();
+
+        Dashboard {
+            user: user.to_owned(),
+            pending,
+            testing,
+            eligible,
+            attention,
+            expiring_days,
+            expiring_overrides,
+        }
+    }
+}
+
+fn write_section(f: &mut Formatter, heading: &str, items: &[DashboardUpdate]) -> std::fmt::Result {
+    writeln!(f, "{} ({}):", heading, items.len())?;
+
+    if items.is_empty() {
+        writeln!(f, "  (None)")?;
+    }
+
+    for item in items {
+        writeln!(f, "  {}", item)?;
+    }
+
+    writeln!(f)
+}
+
+impl Display for Dashboard {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "Dashboard for {}", &self.user)?;
+        writeln!(f)?;
+
+        write_section(f, "Pending updates", &self.pending)?;
+        write_section(f, "Updates in testing", &self.testing)?;
+        write_section(f, "Eligible for stable", &self.eligible)?;
+        write_section(f, "Needs attention", &self.attention)?;
+
+        writeln!(
+            f,
+            "Overrides expiring within {} days ({}):",
+            self.expiring_days,
+            self.expiring_overrides.len()
+        )?;
+
+        if self.expiring_overrides.is_empty() {
+            writeln!(f, "  (None)")?;
+        }
+
+        for info in &self.expiring_overrides {
+            let seconds = info.remaining_seconds.unwrap_or_default();
+            writeln!(
+                f,
+                "  {}: expires {} ({} days and {} hours remaining)",
+                &info.over_ride.nvr,
+                &info.over_ride.expiration_date,
+                seconds / 86400,
+                (seconds % 86400) / 3600
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Command;
+
+use bodhi::*;
+
+use crate::batch::Batch;
+use crate::cli::{BugKarma, TestCaseKarma};
+use crate::comment::{post_comment, Feedback};
+use crate::diff::ask;
+use crate::dry_run::DryRun;
+use crate::record::{Recorder, RequestKey};
+
+// source packages are listed instead of binary packages, because update builds are source NVRs
+const DEFAULT_RPM_COMMAND: &str = "rpm -qa --queryformat '%{SOURCERPM}\\n'";
+
+/// answer to a karma prompt
+enum Answer {
+    Karma(Karma),
+    Skip,
+    Quit,
+}
+
+/// This function asks for karma until a valid answer is given. Empty answers count as skipping
+/// the question, and closed standard input counts as quitting.
+fn ask_karma(prompt: &str) -> Result<Answer, String> {
+    loop {
+        let answer = match ask(prompt)? {
+            Some(answer) => answer,
+            None => return Ok(Answer::Quit),
+        };
+
+        match answer.to_lowercase().as_str() {
+            "" | "s" | "skip" => return Ok(Answer::Skip),
+            "q" | "quit" => return Ok(Answer::Quit),
+            other => match other.parse::<Karma>() {
+                Ok(karma) => return Ok(Answer::Karma(karma)),
+                Err(_) => println!("Invalid answer: {}", other),
+            },
+        }
+    }
+}
+
+/// This function returns the set of installed packages, read from a file, or from the output of
+/// the given shell command. Both need to list the source RPMs of installed packages (as printed by
+/// the default command), because builds in updates are source NVRs. Binary package names can not
+/// be mapped to them (subpackages can have different names), so they are rejected.
+pub fn installed_packages(file: Option<&Path>, command: Option<&str>) -> Result<HashSet<String>, String> {
+    let output = match file {
+        Some(file) => std::fs::read_to_string(file)
+            .map_err(|error| format!("Failed to read package list from {}: {}", file.display(), error))?,
+        None => {
+            let command = command.unwrap_or(DEFAULT_RPM_COMMAND);
+
+            let output = Command::new("sh")
+                .args(["-c", command])
+                .output()
+                .map_err(|error| format!("Failed to run '{}': {}", command, error))?;
+
+            if !output.status.success() {
+                return Err(format!("Command '{}' exited with {}.", command, output.status));
+            }
+
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        },
+    };
+
+    let mut installed = HashSet::new();
+
+    for line in output.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        // packages that were not built from a source RPM (like GPG keys) have no SOURCERPM
+        if line == "(none)" {
+            continue;
+        }
+
+        match line.strip_suffix(".src.rpm") {
+            Some(nvr) => installed.insert(nvr.to_owned()),
+            None => {
+                return Err(format!(
+                    "Not a source RPM: '{}'. Installed packages need to be listed with: {}",
+                    line, DEFAULT_RPM_COMMAND
+                ))
+            },
+        };
+    }
+
+    if installed.is_empty() {
+        return Err(String::from("No installed packages were found."));
+    }
+
+    Ok(installed)
+}
+
+fn print_update(update: &Update, installed: &[&str]) {
+    println!("Update {} ({})", &update.alias, &update.title);
+    println!("Installed: {}", installed.join(", "));
+
+    match update.stable_karma {
+        Some(stable_karma) => println!(
+            "Karma:     {} (stable at {})",
+            update.karma.unwrap_or_default(),
+            stable_karma
+        ),
+        None => println!("Karma:     {}", update.karma.unwrap_or_default()),
+    }
+
+    println!("Notes:");
+    for line in update.notes.lines() {
+        println!("  {}", line);
+    }
+
+    if !update.bugs.is_empty() {
+        println!("Bugs:");
+        for bug in &update.bugs {
+            println!("  {}: {}", bug.bug_id, bug.title.as_deref().unwrap_or_default());
+        }
+    }
+
+    let test_cases = update.test_cases.as_deref().unwrap_or_default();
+    if !test_cases.is_empty() {
+        println!("Test Cases:");
+        for test_case in test_cases {
+            println!("  {}", &test_case.name);
+        }
+    }
+}
+
+/// This function runs an interactive session for submitting karma and feedback for all updates
+/// in testing that contain installed packages. Updates that the user has already commented on are
+/// skipped.
+pub async fn easy_karma(
+    bodhi: &BodhiClient,
+    recorder: &Recorder,
+    dry_run: Option<&DryRun>,
+    username: &str,
+    release: &FedoraRelease,
+    installed: &HashSet<String>,
+) -> Result<(), String> {
+    let query = UpdateQuery::new()
+        .releases(std::slice::from_ref(release))
+        .status(UpdateStatus::Testing);
+
+    let updates: Vec<Update> = recorder
+        .request(
+            &RequestKey::query("/updates/", &UpdatePageQuery::from_query(&query, 0))?,
+            bodhi.paginated_request(&query),
+        )
+        .await?;
+
+    let mut candidates = Vec::new();
+
+    for update in &updates {
+        let nvrs: Vec<&str> = update
+            .builds
+            .iter()
+            .map(|build| build.nvr.as_str())
+            .filter(|nvr| installed.contains(*nvr))
+            .collect();
+
+        if nvrs.is_empty() {
+            continue;
+        }
+
+        let commented = update
+            .comments
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .any(|comment| comment.user.name == username);
+
+        if commented {
+            println!(
+                "Skipping update {}, which you have already commented on.",
+                &update.alias
+            );
+            continue;
+        }
+
+        candidates.push((update, nvrs));
+    }
+
+    if candidates.is_empty() {
+        println!("No updates in testing contain installed packages.");
+        return Ok(());
+    }
+
+    let mut batch = Batch::new();
+    let total = candidates.len();
+
+    for (index, (update, nvrs)) in candidates.into_iter().enumerate() {
+        println!();
+        println!("[{}/{}]", index + 1, total);
+        print_update(update, &nvrs);
+        println!();
+
+        let karma = match ask_karma("Karma? [+1/0/-1, s to skip, q to quit]")? {
+            Answer::Karma(karma) => karma,
+            Answer::Skip => continue,
+            Answer::Quit => break,
+        };
+
+        let mut feedback = Feedback {
+            karma: Some(karma),
+            ..Default::default()
+        };
+
+        let mut quit = false;
+
+        for bug in &update.bugs {
+            if quit {
+                break;
+            }
+
+            match ask_karma(&format!("Feedback for bug {}? [+1/0/-1, empty for none]", bug.bug_id))? {
+                Answer::Karma(karma) => feedback.bugs.push(BugKarma {
+                    bug_id: bug.bug_id,
+                    karma,
+                }),
+                Answer::Skip => {},
+                Answer::Quit => quit = true,
+            }
+        }
+
+        for test_case in update.test_cases.as_deref().unwrap_or_default() {
+            if quit {
+                break;
+            }
+
+            match ask_karma(&format!(
+                "Feedback for test case {}? [+1/0/-1, empty for none]",
+                &test_case.name
+            ))? {
+                Answer::Karma(karma) => feedback.test_cases.push(TestCaseKarma {
+                    name: test_case.name.clone(),
+                    karma,
+                }),
+                Answer::Skip => {},
+                Answer::Quit => quit = true,
+            }
+        }
+
+        if quit {
+            break;
+        }
+
+        let text = ask("Comment (optional):")?.unwrap_or_default();
+
+        if karma == Karma::Neutral && feedback.bugs.is_empty() && feedback.test_cases.is_empty() && text.is_empty() {
+            println!("Nothing to submit for update {}.", &update.alias);
+            continue;
+        }
+
+        batch
+            .run(
+                &update.alias,
+                post_comment(bodhi, recorder, dry_run, update, &text, &feedback),
+            )
+            .await;
+    }
+
+    if batch.is_empty() {
+        println!();
+        println!("No feedback was submitted.");
+        return Ok(());
+    }
+
+    batch.finish()
+}
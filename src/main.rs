@@ -6,20 +6,84 @@ use bodhi::*;
 use clap::Parser;
 use secret_service::{Collection, EncryptionType, SecretService};
 
+pub mod batch;
+pub use batch::*;
+
+pub mod changes;
+pub use changes::*;
+
+pub mod checkpoint;
+pub use checkpoint::*;
+
 pub mod cli;
 pub use cli::*;
 
+pub mod comment;
+pub use comment::*;
+
 pub mod config;
 pub use config::*;
 
+pub mod diff;
+pub use diff::*;
+
+pub mod dry_run;
+pub use dry_run::*;
+
+pub mod info;
+pub use info::*;
+
+pub mod karma;
+pub use karma::*;
+
+pub mod manifest;
+pub use manifest::*;
+
+pub mod notify;
+pub use notify::*;
+
 pub mod output;
 pub use output::*;
 
+pub mod offline;
+pub use offline::*;
+
 pub mod query;
 pub use query::*;
 
+pub mod record;
+pub use record::*;
+
+pub mod store;
+pub use store::*;
+
+pub mod sync;
+pub use sync::*;
+
+pub mod text;
+pub use text::*;
+
+pub mod watch;
+pub use watch::*;
+
 const USER_AGENT: &str = concat!("bodhi-cli v", env!("CARGO_PKG_VERSION"));
 
+// environment variable that is used by the test suite to supply the password of the mock user
+#[cfg(debug_assertions)]
+const TEST_PASSWORD_VAR: &str = "BODHI_CLI_TEST_PASSWORD";
+
+/// This function returns the password that was supplied by the test suite. It is only read in debug
+/// builds, so release builds never take credentials from the environment.
+#[cfg(debug_assertions)]
+fn test_password() -> Option<String> {
+    std::env::var(TEST_PASSWORD_VAR).ok()
+}
+
+#[cfg(not(debug_assertions))]
+fn test_password() -> Option<String> {
+    None
+}
+
 /// This function prompts the user for their FAS password.
 fn read_password() -> String {
     rpassword::prompt_password("FAS Password: ").expect("Failed to read from console.")
@@ -64,7 +128,7 @@ async fn get_store_password(clear: bool) -> Result<String, String> {
     let items = match collection.search_items(attributes.clone()).await {
         Ok(items) => items,
         Err(error) => {
-            format!("Failed to query SecretService: {}", error);
+            println!("Failed to query SecretService: {}", error);
             return Ok(read_password());
         },
     };
@@ -109,6 +173,18 @@ async fn get_store_password(clear: bool) -> Result<String, String> {
 async fn main() -> Result<(), String> {
     let args: BaseCommand = BaseCommand::parse();
     let authenticated = args.authenticated();
+    let server_url = args.server_url().to_owned();
+    let offline = args.offline;
+
+    if offline && authenticated {
+        return Err(String::from(
+            "Commands that modify data on the server can not be used with '--offline'.",
+        ));
+    }
+
+    if offline && !args.offline_capable() {
+        return Err(String::from("This command can not be used with '--offline'."));
+    }
 
     let config = get_config().await?;
 
@@ -121,13 +197,30 @@ async fn main() -> Result<(), String> {
 
     builder = builder.user_agent(USER_AGENT);
 
-    let bodhi = if authenticated {
+    let recorder = match (&args.record, &args.replay) {
+        (None, None) => Recorder::live(),
+        (Some(dir), None) => Recorder::record(dir.to_owned(), &server_url).await?,
+        (None, Some(dir)) => Recorder::replay(dir.to_owned(), &server_url).await?,
+        _ => unreachable!(),
+    };
+
+    // requests that modify data on the server are only shown in dry run mode
+    let dry_run = if args.dry_run && authenticated {
+        Some(DryRun::start(&server_url).await?)
+    } else {
+        None
+    };
+
+    // neither replayed responses nor dry runs require logging in
+    let bodhi = if authenticated && !recorder.replaying() && dry_run.is_none() {
         if args.verbose {
             eprintln!("Authenticating with bodhi ...");
             eprintln!("Username: {}", &config.fas.username);
         }
 
-        let password = if !args.no_store_password {
+        let password = if let Some(password) = test_password() {
+            password
+        } else if !args.no_store_password {
             get_store_password(args.ignore_keyring).await?
         } else {
             read_password()
@@ -140,23 +233,309 @@ async fn main() -> Result<(), String> {
     };
 
     match args.subcommand {
-        BodhiCommand::Comment { update, text, karma } => {
-            let update: Update = bodhi
-                .request(&UpdateIDQuery::new(&update))
-                .await
-                .map_err(|error| error.to_string())?;
+        BodhiCommand::Apply { manifest } => {
+            let manifest = UpdateManifest::load(&manifest)?;
+
+            let builds: Vec<&str> = manifest.builds.iter().map(|b| b.as_str()).collect();
+            let query = UpdateQuery::new().builds(&builds);
+            let updates: Vec<Update> = recorder
+                .request(
+                    &RequestKey::query("/updates/", &UpdatePageQuery::from_query(&query, 0))?,
+                    bodhi.paginated_request(&query),
+                )
+                .await?;
+
+            // obsoleted and unpushed updates can not be edited any more, so they are replaced
+            let mut existing: Vec<Update> = updates
+                .into_iter()
+                .filter(|update| !matches!(update.status, UpdateStatus::Obsolete | UpdateStatus::Unpushed))
+                .collect();
+
+            if existing.len() > 1 {
+                let aliases: Vec<&str> = existing.iter().map(|update| update.alias.as_str()).collect();
+                return Err(format!("Builds are part of multiple updates: {}", aliases.join(", ")));
+            }
 
-            let mut commenter = update.comment().text(&text);
+            let update = existing.pop();
+            let changes = manifest.changes(update.as_ref());
 
-            if let Some(karma) = karma {
-                commenter = commenter.karma(karma);
+            match &update {
+                Some(update) if changes.is_empty() => {
+                    println!("Update {} is up to date.", &update.alias);
+                    return Ok(());
+                },
+                Some(update) => println!("Changes for update {}:", &update.alias),
+                None => println!("Creating new update:"),
             }
 
-            let comment: NewComment = bodhi.request(&commenter).await.map_err(|error| error.to_string())?;
+            print_changes(&changes);
+            println!();
+
+            let requirements = manifest.requirements_string();
+
+            match &update {
+                None => {
+                    let mut builder = UpdateCreator::from_builds(&builds, &manifest.notes);
+
+                    if let Some(autokarma) = manifest.autokarma {
+                        builder = builder.autokarma(autokarma);
+                    };
+
+                    if let Some(autotime) = manifest.autotime {
+                        builder = builder.autotime(autotime);
+                    };
+
+                    if let Some(bugs) = &manifest.bugs {
+                        builder = builder.bugs(bugs);
+                    };
+
+                    if let Some(close_bugs) = manifest.close_bugs {
+                        builder = builder.close_bugs(close_bugs);
+                    };
+
+                    if let Some(display_name) = &manifest.display_name {
+                        builder = builder.display_name(display_name);
+                    };
+
+                    if let Some(require_bugs) = manifest.require_bugs {
+                        builder = builder.require_bugs(require_bugs);
+                    };
+
+                    if let Some(require_testcases) = manifest.require_testcases {
+                        builder = builder.require_testcases(require_testcases);
+                    };
+
+                    if let Some(requirements) = &requirements {
+                        if !requirements.is_empty() {
+                            builder = builder.requirements(requirements);
+                        };
+                    };
+
+                    if let Some(severity) = manifest.severity {
+                        builder = builder.severity(severity);
+                    };
 
-            println!("Comment created.");
-            print_server_msgs(&comment.caveats);
-            println!("{}", &comment.comment);
+                    if let Some(stable_days) = manifest.stable_days {
+                        builder = builder.stable_days(stable_days);
+                    };
+
+                    if let Some(stable_karma) = manifest.stable_karma {
+                        builder = builder.stable_karma(stable_karma);
+                    };
+
+                    if let Some(suggestion) = manifest.suggestion {
+                        builder = builder.suggest(suggestion);
+                    };
+
+                    if let Some(unstable_karma) = manifest.unstable_karma {
+                        builder = builder.unstable_karma(unstable_karma);
+                    };
+
+                    if let Some(update_type) = manifest.update_type {
+                        builder = builder.update_type(update_type);
+                    };
+
+                    if let Some(dry_run) = &dry_run {
+                        return dry_run.show(dry_run.client().request(&builder)).await;
+                    }
+
+                    let result: NewUpdate = recorder
+                        .post(|client| client.request(&builder), bodhi.request(&builder))
+                        .await?;
+
+                    println!("Update created.");
+                    print_server_msgs(&result.caveats);
+                    println!("{}", result.update);
+                },
+                Some(update) => {
+                    let changed = |field: &str| changes.iter().any(|change| change.field == field);
+                    let mut editor = UpdateEditor::from_update(update);
+
+                    if changed("builds") {
+                        for build in &builds {
+                            if !update.builds.iter().any(|b| b.nvr == *build) {
+                                editor = editor.add_build(build);
+                            }
+                        }
+                        for build in &update.builds {
+                            if !builds.contains(&build.nvr.as_str()) {
+                                editor = editor.remove_build(&build.nvr);
+                            }
+                        }
+                    };
+
+                    if changed("notes") {
+                        editor = editor.notes(&manifest.notes);
+                    };
+
+                    if let (true, Some(autokarma)) = (changed("autokarma"), manifest.autokarma) {
+                        editor = editor.autokarma(autokarma);
+                    };
+
+                    if let (true, Some(autotime)) = (changed("autotime"), manifest.autotime) {
+                        editor = editor.autotime(autotime);
+                    };
+
+                    if let (true, Some(bugs)) = (changed("bugs"), &manifest.bugs) {
+                        for bug in bugs {
+                            if !update.bugs.iter().any(|b| b.bug_id == *bug) {
+                                editor = editor.add_bug(*bug);
+                            }
+                        }
+                        for bug in &update.bugs {
+                            if !bugs.contains(&bug.bug_id) {
+                                editor = editor.remove_bug(bug.bug_id);
+                            }
+                        }
+                    };
+
+                    if let (true, Some(close_bugs)) = (changed("close_bugs"), manifest.close_bugs) {
+                        editor = editor.close_bugs(close_bugs);
+                    };
+
+                    if let (true, Some(display_name)) = (changed("display_name"), &manifest.display_name) {
+                        editor = editor.display_name(display_name);
+                    };
+
+                    if let (true, Some(require_bugs)) = (changed("require_bugs"), manifest.require_bugs) {
+                        editor = editor.require_bugs(require_bugs);
+                    };
+
+                    if let (true, Some(require_testcases)) = (changed("require_testcases"), manifest.require_testcases)
+                    {
+                        editor = editor.require_testcases(require_testcases);
+                    };
+
+                    if let (true, Some(requirements)) = (changed("requirements"), &requirements) {
+                        editor = editor.requirements(requirements);
+                    };
+
+                    if let (true, Some(severity)) = (changed("severity"), manifest.severity) {
+                        editor = editor.severity(severity);
+                    };
+
+                    if let (true, Some(stable_days)) = (changed("stable_days"), manifest.stable_days) {
+                        editor = editor.stable_days(stable_days);
+                    };
+
+                    if let (true, Some(stable_karma)) = (changed("stable_karma"), manifest.stable_karma) {
+                        editor = editor.stable_karma(stable_karma);
+                    };
+
+                    if let (true, Some(suggestion)) = (changed("suggestion"), manifest.suggestion) {
+                        editor = editor.suggest(suggestion);
+                    };
+
+                    if let (true, Some(unstable_karma)) = (changed("unstable_karma"), manifest.unstable_karma) {
+                        editor = editor.unstable_karma(unstable_karma);
+                    };
+
+                    if let (true, Some(update_type)) = (changed("type"), manifest.update_type) {
+                        editor = editor.update_type(update_type);
+                    };
+
+                    if let Some(dry_run) = &dry_run {
+                        return dry_run.show(dry_run.client().request(&editor)).await;
+                    }
+
+                    let result: EditedUpdate = recorder
+                        .post(|client| client.request(&editor), bodhi.request(&editor))
+                        .await?;
+
+                    println!("Update edited.");
+                    print_server_msgs(&result.caveats);
+                    println!("{}", result.update);
+                },
+            }
+
+            Ok(())
+        },
+        BodhiCommand::BuildInfo { nvr, format } => {
+            let build: Build = recorder
+                .request(
+                    &RequestKey::get(format!("/builds/{}", &nvr)),
+                    bodhi.request(&BuildNVRQuery::new(&nvr)),
+                )
+                .await?;
+
+            let builds = [nvr.as_str()];
+            let query = UpdateQuery::new().builds(&builds);
+            let updates: Vec<Update> = recorder
+                .request(
+                    &RequestKey::query("/updates/", &UpdatePageQuery::from_query(&query, 0))?,
+                    bodhi.paginated_request(&query),
+                )
+                .await?;
+            let update = updates.into_iter().next();
+
+            let over_ride: Option<Override> = recorder
+                .optional(
+                    &RequestKey::get(format!("/overrides/{}", &nvr)),
+                    bodhi.request(&OverrideNVRQuery::new(&nvr)),
+                )
+                .await?;
+
+            let info = BuildInfo {
+                release: update.as_ref().map(|update| update.release.name.clone()),
+                build,
+                update,
+                over_ride,
+            };
+
+            pretty_output(&info, format.unwrap_or(Format::Plain))?;
+
+            Ok(())
+        },
+        BodhiCommand::Changes => changes(&bodhi, &recorder, &server_url, &config.fas.username).await,
+        BodhiCommand::Comment {
+            update,
+            text,
+            text_file,
+            edit,
+            karma,
+            bug_feedback,
+            testcase_feedback,
+        } => {
+            if text_file.as_ref().is_some_and(|file| file.as_os_str() == "-") && update.iter().any(|u| u == "-") {
+                return Err(String::from("Standard input can only be read once."));
+            }
+
+            let aliases = read_items(&update)?;
+
+            // the same comment text is used for all updates
+            let template = format!("Enter the text of your comment on {}.", aliases.join(", "));
+            let text = text_input(text, text_file, edit, "", &template)?
+                .ok_or_else(|| String::from("No comment text specified."))?;
+
+            let feedback = Feedback {
+                karma,
+                bugs: bug_feedback,
+                test_cases: testcase_feedback,
+            };
+
+            let mut batch = Batch::new();
+
+            for alias in aliases {
+                batch
+                    .run(&alias, async {
+                        let update: Update = query_update(&bodhi, &recorder, &alias).await?;
+
+                        post_comment(&bodhi, &recorder, dry_run.as_ref(), &update, &text, &feedback).await
+                    })
+                    .await;
+            }
+
+            batch.finish()
+        },
+        BodhiCommand::CommentInfo { id, format } => {
+            let result: Comment = recorder
+                .request(
+                    &RequestKey::get(format!("/comments/{}", id)),
+                    bodhi.request(&CommentIDQuery::new(id)),
+                )
+                .await?;
+
+            pretty_output(&result, format.unwrap_or(Format::Plain))?;
 
             Ok(())
         },
@@ -165,32 +544,65 @@ async fn main() -> Result<(), String> {
             request,
             format,
         } => {
-            let result: Compose = bodhi
-                .request(&ComposeReleaseRequestQuery::new(&release, request))
-                .await
-                .map_err(|error| error.to_string())?;
+            let result: Compose = recorder
+                .request(
+                    &RequestKey::get(format!("/composes/{}/{}", &release, request)),
+                    bodhi.request(&ComposeReleaseRequestQuery::new(&release, request)),
+                )
+                .await?;
 
             pretty_output(&result, format.unwrap_or(Format::Plain))?;
 
             Ok(())
         },
         BodhiCommand::ComposeList { format } => {
-            let result: Vec<Compose> = bodhi
-                .request(&ComposeQuery::new())
-                .await
-                .map_err(|error| error.to_string())?;
+            if offline {
+                let store = Store::open(&server_url)?;
+                store.ensure_synced()?;
+
+                pretty_outputs(&store.composes()?, format.unwrap_or(Format::Plain))?;
+                return Ok(());
+            }
+
+            let result: Vec<Compose> = recorder
+                .request(&RequestKey::get("/composes/"), bodhi.request(&ComposeQuery::new()))
+                .await?;
 
             pretty_outputs(&result, format.unwrap_or(Format::Plain))?;
 
             Ok(())
         },
+        BodhiCommand::ComposeWait {
+            release,
+            request,
+            interval,
+            timeout,
+            notify,
+        } => {
+            let polling = Polling {
+                interval: std::time::Duration::from_secs(interval),
+                max_interval: std::time::Duration::from_secs(interval),
+                timeout: timeout.map(std::time::Duration::from_secs),
+            };
+
+            let notifier = if notify { Notifier::connect().await } else { None };
+
+            wait_for_compose(&bodhi, &recorder, &release, request, &polling, notifier.as_ref()).await
+        },
         BodhiCommand::CreateOverride { nvr, duration, notes } => {
             let current_date = chrono::Utc::now();
             let expiration_date = (current_date + chrono::Duration::days(duration as i64)).into();
 
             let creator = OverrideCreator::new(&nvr, &notes, &expiration_date);
 
-            match bodhi.request(&creator).await {
+            if let Some(dry_run) = &dry_run {
+                return dry_run.show(dry_run.client().request(&creator)).await;
+            }
+
+            match recorder
+                .post::<NewOverride, _, _, _>(|client| client.request(&creator), bodhi.request(&creator))
+                .await
+            {
                 Ok(result) => {
                     println!("Successfully created override for: {}", &result.over_ride.nvr);
                     print_server_msgs(&result.caveats);
@@ -198,36 +610,61 @@ async fn main() -> Result<(), String> {
                 },
                 Err(error) => {
                     println!("Failed to create override for {}, aborting.", &nvr);
-                    Err(error.to_string())
+                    Err(error)
                 },
             }
         },
-        BodhiCommand::CreateUpdateOverride { alias, duration, notes } => {
-            let update = query_update(&bodhi, &alias).await?;
-
+        BodhiCommand::CreateUpdateOverride {
+            aliases,
+            duration,
+            notes,
+        } => {
             let current_date = chrono::Utc::now();
             let expiration_date = (current_date + chrono::Duration::days(duration as i64)).into();
 
-            let mut result = Ok(());
-
-            for build in &update.builds {
-                let creator = OverrideCreator::new(&build.nvr, &notes, &expiration_date);
-
-                match bodhi.request(&creator).await {
-                    Ok(result) => {
-                        println!(" - successfully created override for: {}", &result.over_ride.nvr);
-                        print_server_msgs(&result.caveats);
-                        continue;
-                    },
-                    Err(error) => {
-                        println!(" - failed to create override for {}, aborting.", &build.nvr);
-                        result = Err(format!("{}", error));
-                        break;
-                    },
-                }
+            let mut batch = Batch::new();
+
+            for alias in read_items(&aliases)? {
+                batch
+                    .run(&alias, async {
+                        let update = query_update(&bodhi, &recorder, &alias).await?;
+
+                        let mut result = Ok(());
+
+                        for build in &update.builds {
+                            let creator = OverrideCreator::new(&build.nvr, &notes, &expiration_date);
+
+                            if let Some(dry_run) = &dry_run {
+                                dry_run.show(dry_run.client().request(&creator)).await?;
+                                continue;
+                            }
+
+                            match recorder
+                                .post::<NewOverride, _, _, _>(
+                                    |client| client.request(&creator),
+                                    bodhi.request(&creator),
+                                )
+                                .await
+                            {
+                                Ok(result) => {
+                                    println!(" - successfully created override for: {}", &result.over_ride.nvr);
+                                    print_server_msgs(&result.caveats);
+                                    continue;
+                                },
+                                Err(error) => {
+                                    println!(" - failed to create override for {}, aborting.", &build.nvr);
+                                    result = Err(error);
+                                    break;
+                                },
+                            }
+                        }
+
+                        result
+                    })
+                    .await;
             }
 
-            result
+            batch.finish()
         },
         BodhiCommand::CreateUpdate {
             autokarma,
@@ -238,6 +675,8 @@ async fn main() -> Result<(), String> {
             display_name,
             from_tag,
             notes,
+            notes_file,
+            edit,
             require_bugs,
             require_testcases,
             requirements,
@@ -248,6 +687,9 @@ async fn main() -> Result<(), String> {
             unstable_karma,
             update_type,
         } => {
+            let notes = text_input(notes, notes_file, edit, "", "Enter the notes for the new update.")?
+                .ok_or_else(|| String::from("No update notes specified."))?;
+
             let builds: Option<Vec<&str>> = builds
                 .as_ref()
                 .map(|builds| builds.iter().map(|b| b.as_str()).collect());
@@ -318,7 +760,13 @@ async fn main() -> Result<(), String> {
                 builder = builder.update_type(update_type);
             };
 
-            let result: NewUpdate = bodhi.request(&builder).await.map_err(|error| error.to_string())?;
+            if let Some(dry_run) = &dry_run {
+                return dry_run.show(dry_run.client().request(&builder)).await;
+            }
+
+            let result: NewUpdate = recorder
+                .post(|client| client.request(&builder), bodhi.request(&builder))
+                .await?;
 
             println!("Update created.");
             print_server_msgs(&result.caveats);
@@ -326,22 +774,102 @@ async fn main() -> Result<(), String> {
 
             Ok(())
         },
-        BodhiCommand::EditOverride { nvr, duration, notes } => {
+        BodhiCommand::Dashboard { expiring_days, format } => {
+            let username = &config.fas.username;
+
+            let updates = query_user_updates(
+                &bodhi,
+                &recorder,
+                username,
+                &[UpdateStatus::Pending, UpdateStatus::Testing],
+            )
+            .await?;
+            let overrides = query_user_overrides(&bodhi, &recorder, username).await?;
+
+            let dashboard = Dashboard::new(username, &updates, overrides, expiring_days, chrono::Utc::now());
+            pretty_output(&dashboard, format.unwrap_or(Format::Plain))?;
+
+            Ok(())
+        },
+        BodhiCommand::EasyKarma {
+            release,
+            rpm_list,
+            rpm_command,
+        } => {
+            let installed = installed_packages(rpm_list.as_deref(), rpm_command.as_deref())?;
+
+            easy_karma(
+                &bodhi,
+                &recorder,
+                dry_run.as_ref(),
+                &config.fas.username,
+                &release,
+                &installed,
+            )
+            .await
+        },
+        BodhiCommand::EditOverride {
+            nvrs,
+            duration,
+            notes,
+            yes,
+        } => {
             let current_date = chrono::Utc::now();
             let expiration_date = (current_date + chrono::Duration::days(duration as i64)).into();
 
-            let over_ride = query_override(&bodhi, &nvr).await?;
-            let editor = OverrideEditor::from_override(&over_ride)
-                .expiration_date(&expiration_date)
-                .notes(&notes);
+            // confirmation prompts are read from standard input as well
+            if dry_run.is_none() && !yes && nvrs.iter().any(|nvr| nvr == "-") {
+                return Err(String::from(
+                    "Reading NVRs from standard input requires the '--yes' flag.",
+                ));
+            }
 
-            let result: EditedOverride = bodhi.request(&editor).await.map_err(|error| error.to_string())?;
+            let mut batch = Batch::new();
 
-            println!("Override edited.");
-            print_server_msgs(&result.caveats);
-            println!("{}", result.over_ride);
+            for nvr in read_items(&nvrs)? {
+                batch
+                    .run(&nvr, async {
+                        let over_ride = query_override(&bodhi, &recorder, &nvr).await?;
 
-            Ok(())
+                        let changes = override_changes(&over_ride, &notes, &expiration_date);
+                        if changes.is_empty() {
+                            println!("Nothing to change for override {}.", &nvr);
+                            return Ok(());
+                        }
+
+                        println!("Changes for override {}:", &nvr);
+                        print_changes(&changes);
+                        println!();
+
+                        if dry_run.is_none() && !yes {
+                            confirm("Apply these changes?")?;
+                        }
+
+                        let mut editor = OverrideEditor::from_override(&over_ride).notes(&notes);
+
+                        // the expiration date is only changed if it is on a different day
+                        if changes.iter().any(|change| change.field == "expiration_date") {
+                            editor = editor.expiration_date(&expiration_date);
+                        }
+
+                        if let Some(dry_run) = &dry_run {
+                            return dry_run.show(dry_run.client().request(&editor)).await;
+                        }
+
+                        let result: EditedOverride = recorder
+                            .post(|client| client.request(&editor), bodhi.request(&editor))
+                            .await?;
+
+                        println!("Override edited.");
+                        print_server_msgs(&result.caveats);
+                        println!("{}", result.over_ride);
+
+                        Ok(())
+                    })
+                    .await;
+            }
+
+            batch.finish()
         },
         BodhiCommand::EditUpdate {
             alias,
@@ -352,6 +880,8 @@ async fn main() -> Result<(), String> {
             close_bugs,
             display_name,
             notes,
+            notes_file,
+            edit,
             remove_bugs,
             remove_builds,
             requirements,
@@ -361,8 +891,70 @@ async fn main() -> Result<(), String> {
             suggestion,
             unstable_karma,
             update_type,
+            yes,
         } => {
-            let update = query_update(&bodhi, &alias).await?;
+            // confirmation prompts are read from standard input as well
+            if dry_run.is_none() && !yes && notes_file.as_ref().is_some_and(|path| path.as_os_str() == "-") {
+                return Err(String::from(
+                    "Reading notes from standard input requires the '--yes' flag.",
+                ));
+            }
+
+            let update = query_update(&bodhi, &recorder, &alias).await?;
+
+            let template = format!("Edit the notes of update {}.", &update.alias);
+            let notes = text_input(notes, notes_file, edit, &update.notes, &template)?;
+
+            let mut new_builds: Vec<String> = update.builds.iter().map(|b| b.nvr.clone()).collect();
+            for build in add_builds.iter().flatten() {
+                if !new_builds.contains(build) {
+                    new_builds.push(build.clone());
+                }
+            }
+            new_builds.retain(|build| !remove_builds.iter().flatten().any(|removed| removed == build));
+
+            let mut new_bugs: Vec<u32> = update.bugs.iter().map(|b| b.bug_id).collect();
+            for bug in add_bugs.iter().flatten() {
+                if !new_bugs.contains(bug) {
+                    new_bugs.push(*bug);
+                }
+            }
+            new_bugs.retain(|bug| !remove_bugs.iter().flatten().any(|removed| removed == bug));
+
+            // the state of the update after editing, for comparing it with the current state
+            let edited = UpdateManifest {
+                builds: new_builds,
+                notes: notes.clone().unwrap_or_else(|| update.notes.clone()),
+                autokarma,
+                autotime,
+                bugs: Some(new_bugs),
+                close_bugs,
+                display_name: display_name.clone(),
+                require_bugs: None,
+                require_testcases: None,
+                requirements: requirements.clone(),
+                severity,
+                stable_days,
+                stable_karma,
+                suggestion,
+                unstable_karma,
+                update_type,
+            };
+
+            let changes = edited.changes(Some(&update));
+            if changes.is_empty() {
+                println!("Nothing to change for update {}.", &update.alias);
+                return Ok(());
+            }
+
+            println!("Changes for update {}:", &update.alias);
+            print_changes(&changes);
+            println!();
+
+            if dry_run.is_none() && !yes {
+                confirm("Apply these changes?")?;
+            }
+
             let mut editor = UpdateEditor::from_update(&update);
 
             if let Some(add_bugs) = add_bugs {
@@ -438,7 +1030,13 @@ async fn main() -> Result<(), String> {
                 editor = editor.update_type(update_type);
             }
 
-            let result: EditedUpdate = bodhi.request(&editor).await.map_err(|error| error.to_string())?;
+            if let Some(dry_run) = &dry_run {
+                return dry_run.show(dry_run.client().request(&editor)).await;
+            }
+
+            let result: EditedUpdate = recorder
+                .post(|client| client.request(&editor), bodhi.request(&editor))
+                .await?;
 
             println!("Update edited.");
             print_server_msgs(&result.caveats);
@@ -446,15 +1044,226 @@ async fn main() -> Result<(), String> {
 
             Ok(())
         },
-        BodhiCommand::ExpireOverride { nvr } => {
-            let over_ride = query_override(&bodhi, &nvr).await?;
-            let editor = OverrideEditor::from_override(&over_ride).expired(true);
+        BodhiCommand::ExpireOverride { nvrs } => {
+            let mut batch = Batch::new();
 
-            let result: EditedOverride = bodhi.request(&editor).await.map_err(|error| error.to_string())?;
+            for nvr in read_items(&nvrs)? {
+                batch
+                    .run(&nvr, async {
+                        let over_ride = query_override(&bodhi, &recorder, &nvr).await?;
+                        let editor = OverrideEditor::from_override(&over_ride).expired(true);
 
-            println!("Override expired.");
-            print_server_msgs(&result.caveats);
-            println!("{}", result.over_ride);
+                        if let Some(dry_run) = &dry_run {
+                            return dry_run.show(dry_run.client().request(&editor)).await;
+                        }
+
+                        let result: EditedOverride = recorder
+                            .post(|client| client.request(&editor), bodhi.request(&editor))
+                            .await?;
+
+                        println!("Override expired.");
+                        print_server_msgs(&result.caveats);
+                        println!("{}", result.over_ride);
+
+                        Ok(())
+                    })
+                    .await;
+            }
+
+            batch.finish()
+        },
+        BodhiCommand::OverrideInfo { nvr, format } => {
+            let over_ride = query_override(&bodhi, &recorder, &nvr).await?;
+
+            pretty_output(
+                &OverrideInfo::new(over_ride, chrono::Utc::now()),
+                format.unwrap_or(Format::Plain),
+            )?;
+
+            Ok(())
+        },
+        BodhiCommand::PushEligible { yes } => {
+            let updates = query_user_updates(&bodhi, &recorder, &config.fas.username, &[UpdateStatus::Testing]).await?;
+
+            let now = chrono::Utc::now();
+            let eligible: Vec<(&Update, String)> = updates
+                .iter()
+                .filter_map(|update| stable_reason(update, now).map(|reason| (update, reason)))
+                .collect();
+
+            if eligible.is_empty() {
+                println!("No updates are eligible for stable.");
+                return Ok(());
+            }
+
+            println!("Updates eligible for stable:");
+            for (update, reason) in &eligible {
+                println!("  {} ({}): {}", &update.alias, &update.title, reason);
+            }
+            println!();
+
+            if dry_run.is_none() && !yes {
+                confirm(&format!("Request stable for {} updates?", eligible.len()))?;
+            }
+
+            let mut batch = Batch::new();
+
+            for (update, _) in eligible {
+                batch
+                    .run(&update.alias, async {
+                        let editor = UpdateStatusRequester::from_update(update, UpdateRequest::Stable);
+
+                        if let Some(dry_run) = &dry_run {
+                            return dry_run.show(dry_run.client().request(&editor)).await;
+                        }
+
+                        let _: Update = recorder
+                            .post(|client| client.request(&editor), bodhi.request(&editor))
+                            .await?;
+
+                        println!("Update {} requested for stable.", &update.alias);
+
+                        Ok(())
+                    })
+                    .await;
+            }
+
+            batch.finish()
+        },
+        BodhiCommand::QueryBuilds {
+            nvr,
+            packages,
+            format,
+            releases,
+            updates,
+            force,
+            resume,
+        } => {
+            let format = format.unwrap_or(Format::Plain);
+
+            let pkg_refs: Option<Vec<&str>> = packages.as_ref().map(|ps| ps.iter().map(|p| p.as_str()).collect());
+            let update_refs: Option<Vec<&str>> = updates.as_ref().map(|us| us.iter().map(|u| u.as_str()).collect());
+
+            let mut query = BuildQuery::new().rows_per_page(PAGE_ROWS);
+            let mut long_running = true;
+
+            if let Some(nvr) = &nvr {
+                query = query.nvr(nvr);
+                long_running = false;
+            };
+
+            if let Some(pkg_refs) = &pkg_refs {
+                query = query.packages(pkg_refs);
+                long_running = false;
+            };
+
+            if let Some(releases) = &releases {
+                query = query.releases(releases);
+                long_running = false;
+            };
+
+            if let Some(update_refs) = &update_refs {
+                query = query.updates(update_refs);
+                long_running = false;
+            };
+
+            if long_running && !force {
+                eprintln!("Querying builds without filters takes a *long* time. This is probably not");
+                eprintln!("what you want to do. To do it anyway, use the '--force' flag.");
+
+                return Ok(());
+            }
+
+            let (bodhi, query) = (&bodhi, &query);
+            let result: Vec<Build> = checkpointed_query(
+                &recorder,
+                &server_url,
+                "/builds/",
+                resume,
+                matches!(format, Format::Plain),
+                |page| BuildPageQuery::from_query(query, page),
+                |page_query| async move { bodhi.request(&page_query).await },
+            )
+            .await?;
+
+            pretty_outputs(&result, format)?;
+
+            Ok(())
+        },
+        BodhiCommand::QueryComments {
+            format,
+            ignore_users,
+            packages,
+            since,
+            update_owners,
+            updates,
+            users,
+            force,
+            resume,
+        } => {
+            let format = format.unwrap_or(Format::Plain);
+
+            let ignore_refs: Option<Vec<&str>> =
+                ignore_users.as_ref().map(|us| us.iter().map(|u| u.as_str()).collect());
+            let pkg_refs: Option<Vec<&str>> = packages.as_ref().map(|ps| ps.iter().map(|p| p.as_str()).collect());
+            let owner_refs: Option<Vec<&str>> =
+                update_owners.as_ref().map(|us| us.iter().map(|u| u.as_str()).collect());
+            let update_refs: Option<Vec<&str>> = updates.as_ref().map(|us| us.iter().map(|u| u.as_str()).collect());
+            let user_refs: Option<Vec<&str>> = users.as_ref().map(|us| us.iter().map(|u| u.as_str()).collect());
+
+            let mut query = CommentQuery::new().rows_per_page(PAGE_ROWS);
+            let mut long_running = true;
+
+            // ignoring some users does not make a query any shorter
+            if let Some(ignore_refs) = &ignore_refs {
+                query = query.ignore_users(ignore_refs);
+            };
+
+            if let Some(pkg_refs) = &pkg_refs {
+                query = query.packages(pkg_refs);
+                long_running = false;
+            };
+
+            if let Some(since) = &since {
+                query = query.since(since);
+                long_running = false;
+            };
+
+            if let Some(owner_refs) = &owner_refs {
+                query = query.update_owners(owner_refs);
+                long_running = false;
+            };
+
+            if let Some(update_refs) = &update_refs {
+                query = query.updates(update_refs);
+                long_running = false;
+            };
+
+            if let Some(user_refs) = &user_refs {
+                query = query.users(user_refs);
+                long_running = false;
+            };
+
+            if long_running && !force {
+                eprintln!("Querying comments without filters takes a *long* time. This is probably not");
+                eprintln!("what you want to do. To do it anyway, use the '--force' flag.");
+
+                return Ok(());
+            }
+
+            let (bodhi, query) = (&bodhi, &query);
+            let result: Vec<Comment> = checkpointed_query(
+                &recorder,
+                &server_url,
+                "/comments/",
+                resume,
+                matches!(format, Format::Plain),
+                |page| CommentPageQuery::from_query(query, page),
+                |page_query| async move { bodhi.request(&page_query).await },
+            )
+            .await?;
+
+            pretty_outputs(&result, format)?;
 
             Ok(())
         },
@@ -465,13 +1274,36 @@ async fn main() -> Result<(), String> {
             releases,
             users,
             force,
+            resume,
         } => {
             let format = format.unwrap_or(Format::Plain);
 
+            if offline {
+                let store = Store::open(&server_url)?;
+                store.ensure_synced()?;
+
+                let filter = OverrideFilter {
+                    builds: builds.as_deref(),
+                    expired,
+                    releases: releases.as_deref(),
+                    users: users.as_deref(),
+                };
+
+                let result: Vec<Override> = store
+                    .overrides()?
+                    .into_iter()
+                    .filter(|(over_ride, release)| filter.matches(over_ride, release.as_deref()))
+                    .map(|(over_ride, _)| over_ride)
+                    .collect();
+
+                pretty_outputs(&result, format)?;
+                return Ok(());
+            }
+
             let build_refs: Option<Vec<&str>> = builds.as_ref().map(|bs| bs.iter().map(|b| b.as_str()).collect());
             let user_refs: Option<Vec<&str>> = users.as_ref().map(|us| us.iter().map(|u| u.as_str()).collect());
 
-            let mut query = OverrideQuery::new();
+            let mut query = OverrideQuery::new().rows_per_page(PAGE_ROWS);
             let mut long_running = true;
 
             if let Some(build_refs) = &build_refs {
@@ -494,21 +1326,75 @@ async fn main() -> Result<(), String> {
                 long_running = false;
             };
 
-            if let Format::Plain = format {
-                query = query.callback(progress_bar)
+            if long_running && !force {
+                eprintln!("Querying overrides without filters takes a *long* time. This is probably not");
+                eprintln!("what you want to do. To do it anyway, use the '--force' flag.");
+
+                return Ok(());
+            }
+
+            let (bodhi, query) = (&bodhi, &query);
+            let result: Vec<Override> = checkpointed_query(
+                &recorder,
+                &server_url,
+                "/overrides/",
+                resume,
+                matches!(format, Format::Plain),
+                |page| OverridePageQuery::from_query(query, page),
+                |page_query| async move { bodhi.request(&page_query).await },
+            )
+            .await?;
+
+            pretty_outputs(&result, format)?;
+
+            Ok(())
+        },
+        BodhiCommand::QueryPackages {
+            format,
+            like,
+            name,
+            search,
+            force,
+            resume,
+        } => {
+            let format = format.unwrap_or(Format::Plain);
+
+            let mut query = PackageQuery::new().rows_per_page(PAGE_ROWS);
+            let mut long_running = true;
+
+            if let Some(like) = &like {
+                query = query.like(like);
+                long_running = false;
+            };
+
+            if let Some(name) = &name {
+                query = query.name(name);
+                long_running = false;
+            };
+
+            if let Some(search) = &search {
+                query = query.search(search);
+                long_running = false;
             };
 
             if long_running && !force {
-                eprintln!("Querying overrides without filters takes a *long* time. This is probably not");
+                eprintln!("Querying packages without filters takes a *long* time. This is probably not");
                 eprintln!("what you want to do. To do it anyway, use the '--force' flag.");
 
                 return Ok(());
             }
 
-            let result: Vec<Override> = bodhi
-                .paginated_request(&query)
-                .await
-                .map_err(|error| error.to_string())?;
+            let (bodhi, query) = (&bodhi, &query);
+            let result: Vec<Package> = checkpointed_query(
+                &recorder,
+                &server_url,
+                "/packages/",
+                resume,
+                matches!(format, Format::Plain),
+                |page| PackagePageQuery::from_query(query, page),
+                |page_query| async move { bodhi.request(&page_query).await },
+            )
+            .await?;
 
             pretty_outputs(&result, format)?;
 
@@ -521,6 +1407,7 @@ async fn main() -> Result<(), String> {
             critpath,
             content_type,
             format,
+            fields,
             locked,
             modified_before,
             modified_since,
@@ -538,15 +1425,57 @@ async fn main() -> Result<(), String> {
             update_type,
             users,
             force,
+            resume,
         } => {
             let format = format.unwrap_or(Format::Plain);
 
+            if offline {
+                let store = Store::open(&server_url)?;
+                store.ensure_synced()?;
+
+                let filter = UpdateFilter {
+                    alias: alias.as_deref(),
+                    bugs: bugs.as_deref(),
+                    builds: builds.as_deref(),
+                    critpath,
+                    content_type,
+                    locked,
+                    modified_before: modified_before.as_ref(),
+                    modified_since: modified_since.as_ref(),
+                    packages: packages.as_deref(),
+                    pushed,
+                    pushed_before: pushed_before.as_ref(),
+                    pushed_since: pushed_since.as_ref(),
+                    releases: releases.as_deref(),
+                    request,
+                    severity,
+                    status,
+                    submitted_before: submitted_before.as_ref(),
+                    submitted_since: submitted_since.as_ref(),
+                    suggestion,
+                    update_type,
+                    users: users.as_deref(),
+                };
+
+                let result: Vec<Update> = store
+                    .updates()?
+                    .into_iter()
+                    .filter(|update| filter.matches(update))
+                    .collect();
+
+                match &fields {
+                    Some(fields) => field_outputs(&result, fields)?,
+                    None => pretty_outputs(&result, format)?,
+                }
+                return Ok(());
+            }
+
             let aliases = alias.as_ref().map(|alias| vec![alias.as_str()]);
             let build_refs: Option<Vec<&str>> = builds.as_ref().map(|bs| bs.iter().map(|b| b.as_str()).collect());
             let pkg_refs: Option<Vec<&str>> = packages.as_ref().map(|ps| ps.iter().map(|p| p.as_str()).collect());
             let user_refs: Option<Vec<&str>> = users.as_ref().map(|us| us.iter().map(|u| u.as_str()).collect());
 
-            let mut query = UpdateQuery::new();
+            let mut query = UpdateQuery::new().rows_per_page(PAGE_ROWS);
             let mut long_running = true;
 
             if let Some(aliases) = &aliases {
@@ -654,74 +1583,267 @@ async fn main() -> Result<(), String> {
                 long_running = false;
             };
 
-            if let Format::Plain = format {
-                query = query.callback(progress_bar)
+            if long_running && !force {
+                eprintln!("Querying updates without filters takes a *long* time. This is probably not");
+                eprintln!("what you want to do. To do it anyway, use the '--force' flag.");
+
+                return Ok(());
+            }
+
+            let (bodhi, query) = (&bodhi, &query);
+            let result: Vec<Update> = checkpointed_query(
+                &recorder,
+                &server_url,
+                "/updates/",
+                resume,
+                matches!(format, Format::Plain) && fields.is_none(),
+                |page| UpdatePageQuery::from_query(query, page),
+                |page_query| async move { bodhi.request(&page_query).await },
+            )
+            .await?;
+
+            match &fields {
+                Some(fields) => field_outputs(&result, fields)?,
+                None => pretty_outputs(&result, format)?,
+            }
+
+            Ok(())
+        },
+        BodhiCommand::QueryUsers {
+            format,
+            groups,
+            like,
+            name,
+            force,
+            resume,
+        } => {
+            let format = format.unwrap_or(Format::Plain);
+
+            let group_refs: Option<Vec<&str>> = groups.as_ref().map(|gs| gs.iter().map(|g| g.as_str()).collect());
+
+            let mut query = UserQuery::new().rows_per_page(PAGE_ROWS);
+            let mut long_running = true;
+
+            if let Some(group_refs) = &group_refs {
+                query = query.groups(group_refs);
+                long_running = false;
+            };
+
+            if let Some(like) = &like {
+                query = query.like(like);
+                long_running = false;
+            };
+
+            if let Some(name) = &name {
+                query = query.name(name);
+                long_running = false;
             };
 
             if long_running && !force {
-                eprintln!("Querying updates without filters takes a *long* time. This is probably not");
+                eprintln!("Querying users without filters takes a *long* time. This is probably not");
                 eprintln!("what you want to do. To do it anyway, use the '--force' flag.");
 
                 return Ok(());
             }
 
-            let result: Vec<Update> = bodhi
-                .paginated_request(&query)
-                .await
-                .map_err(|error| error.to_string())?;
+            let (bodhi, query) = (&bodhi, &query);
+            let result: Vec<User> = checkpointed_query(
+                &recorder,
+                &server_url,
+                "/users/",
+                resume,
+                matches!(format, Format::Plain),
+                |page| UserPageQuery::from_query(query, page),
+                |page_query| async move { bodhi.request(&page_query).await },
+            )
+            .await?;
 
             pretty_outputs(&result, format)?;
 
             Ok(())
         },
         BodhiCommand::ReleaseInfo { release, format } => {
-            let result: Release = bodhi
-                .request(&ReleaseNameQuery::new(&release))
-                .await
-                .map_err(|error| error.to_string())?;
+            if offline {
+                let store = Store::open(&server_url)?;
+                store.ensure_synced()?;
+
+                let result = match store.release(&release)? {
+                    Some(result) => result,
+                    None => return Err(format!("Release {} not found in local database.", &release)),
+                };
+
+                pretty_output(&result, format.unwrap_or(Format::Plain))?;
+                return Ok(());
+            }
+
+            let result: Release = recorder
+                .request(
+                    &RequestKey::get(format!("/releases/{}", &release)),
+                    bodhi.request(&ReleaseNameQuery::new(&release)),
+                )
+                .await?;
 
             pretty_output(&result, format.unwrap_or(Format::Plain))?;
 
             Ok(())
         },
         BodhiCommand::ReleaseList { format } => {
-            let result: Vec<Release> = bodhi
-                .paginated_request(&ReleaseQuery::new())
-                .await
-                .map_err(|error| error.to_string())?;
+            if offline {
+                let store = Store::open(&server_url)?;
+                store.ensure_synced()?;
+
+                pretty_outputs(&store.releases()?, format.unwrap_or(Format::Plain))?;
+                return Ok(());
+            }
+
+            let result: Vec<Release> = recorder
+                .request(
+                    &RequestKey::query("/releases/", &ReleasePageQuery::from_query(&ReleaseQuery::new(), 0))?,
+                    bodhi.paginated_request(&ReleaseQuery::new()),
+                )
+                .await?;
 
             pretty_outputs(&result, format.unwrap_or(Format::Plain))?;
 
             Ok(())
         },
-        BodhiCommand::UpdateRequest { alias, request } => {
-            let update: Update = query_update(&bodhi, &alias).await?;
-            let editor = UpdateStatusRequester::from_update(&update, request);
+        BodhiCommand::Sql { query, format } => {
+            let store = Store::open(&server_url)?;
+            store.ensure_synced()?;
 
-            let result: Update = bodhi.request(&editor).await.map_err(|error| error.to_string())?;
+            pretty_outputs(&store.query(&query)?, format.unwrap_or(Format::Plain))?;
 
-            println!("Update requested for {}.", request);
-            println!("{}", result);
+            Ok(())
+        },
+        BodhiCommand::Sync { releases, full, resume } => {
+            let store = Store::open(&server_url)?;
+            sync(
+                &bodhi,
+                &recorder,
+                &store,
+                &server_url,
+                releases.as_deref(),
+                full,
+                resume,
+            )
+            .await?;
+
+            if args.verbose {
+                for (table, count) in store.counts()? {
+                    println!("{:<10} {}", table, count);
+                }
+            }
 
             Ok(())
         },
-        BodhiCommand::WaiveTests { alias, comment, tests } => {
-            let update = query_update(&bodhi, &alias).await?;
+        BodhiCommand::UpdateInfo { alias, format } => {
+            let update: Update = query_update(&bodhi, &recorder, &alias).await?;
+
+            pretty_output(&UpdateInfo { update }, format.unwrap_or(Format::Plain))?;
+
+            Ok(())
+        },
+        BodhiCommand::UpdateRequest { aliases, request } => {
+            let mut batch = Batch::new();
+
+            for alias in read_items(&aliases)? {
+                batch
+                    .run(&alias, async {
+                        let update: Update = query_update(&bodhi, &recorder, &alias).await?;
+                        let editor = UpdateStatusRequester::from_update(&update, request);
 
-            let test_refs: Option<Vec<&str>> = tests.as_ref().map(|ts| ts.iter().map(|t| t.as_str()).collect());
+                        if let Some(dry_run) = &dry_run {
+                            return dry_run.show(dry_run.client().request(&editor)).await;
+                        }
 
-            let mut editor = UpdateTestResultWaiver::from_update(&update, &comment);
+                        let result: Update = recorder
+                            .post(|client| client.request(&editor), bodhi.request(&editor))
+                            .await?;
 
-            if let Some(test_refs) = &test_refs {
-                editor = editor.tests(test_refs)
+                        println!("Update requested for {}.", request);
+                        println!("{}", result);
+
+                        Ok(())
+                    })
+                    .await;
             }
 
-            let result: Update = bodhi.request(&editor).await.map_err(|error| error.to_string())?;
+            batch.finish()
+        },
+        BodhiCommand::UserInfo { name, format } => {
+            let result: User = recorder
+                .request(
+                    &RequestKey::get(format!("/users/{}", &name)),
+                    bodhi.request(&UserNameQuery::new(&name)),
+                )
+                .await?;
 
-            println!("Tests waived.");
-            println!("{}", result);
+            pretty_output(&result, format.unwrap_or(Format::Plain))?;
 
             Ok(())
         },
+        BodhiCommand::WaiveTests {
+            aliases,
+            comment,
+            tests,
+        } => {
+            let mut batch = Batch::new();
+
+            for alias in read_items(&aliases)? {
+                batch
+                    .run(&alias, async {
+                        let update = query_update(&bodhi, &recorder, &alias).await?;
+
+                        let test_refs: Option<Vec<&str>> =
+                            tests.as_ref().map(|ts| ts.iter().map(|t| t.as_str()).collect());
+
+                        let mut editor = UpdateTestResultWaiver::from_update(&update, &comment);
+
+                        if let Some(test_refs) = &test_refs {
+                            editor = editor.tests(test_refs)
+                        }
+
+                        if let Some(dry_run) = &dry_run {
+                            return dry_run.show(dry_run.client().request(&editor)).await;
+                        }
+
+                        let result: Update = recorder
+                            .post(|client| client.request(&editor), bodhi.request(&editor))
+                            .await?;
+
+                        println!("Tests waived.");
+                        println!("{}", result);
+
+                        Ok(())
+                    })
+                    .await;
+            }
+
+            batch.finish()
+        },
+        BodhiCommand::Watch {
+            alias,
+            until,
+            interval,
+            max_interval,
+            timeout,
+            notify,
+        } => {
+            let polling = Polling {
+                interval: std::time::Duration::from_secs(interval),
+                max_interval: std::time::Duration::from_secs(max_interval),
+                timeout: timeout.map(std::time::Duration::from_secs),
+            };
+
+            let notifier = if notify { Notifier::connect().await } else { None };
+
+            let outcome = watch_update(&bodhi, &recorder, &alias, until, &polling, notifier.as_ref()).await?;
+
+            // outcomes other than reaching the target state have distinct exit codes
+            match outcome.exit_code() {
+                0 => Ok(()),
+                code => std::process::exit(code),
+            }
+        },
     }
 }
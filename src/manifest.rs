@@ -0,0 +1,218 @@
+use std::fmt::Display;
+use std::path::Path;
+
+use bodhi::*;
+use serde::Deserialize;
+
+use crate::diff::Change;
+use crate::info::parse_date;
+
+/// declarative description of an update, as read from a TOML manifest file
+///
+/// Only `builds` and `notes` are mandatory. Fields that are not present in the manifest are left
+/// at their server-side defaults when creating an update, and are not changed when editing one.
+/// The same type is used for describing the result of `edit-update` before it is applied.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateManifest {
+    pub builds: Vec<String>,
+    pub notes: String,
+    pub autokarma: Option<bool>,
+    pub autotime: Option<bool>,
+    pub bugs: Option<Vec<u32>>,
+    pub close_bugs: Option<bool>,
+    pub display_name: Option<String>,
+    pub require_bugs: Option<bool>,
+    pub require_testcases: Option<bool>,
+    pub requirements: Option<Vec<String>>,
+    pub severity: Option<UpdateSeverity>,
+    pub stable_days: Option<u32>,
+    pub stable_karma: Option<i32>,
+    pub suggestion: Option<UpdateSuggestion>,
+    pub unstable_karma: Option<i32>,
+    #[serde(rename = "type")]
+    pub update_type: Option<UpdateType>,
+}
+
+// requirements are stored as a comma- or space-separated string on the server
+fn normalize_requirements<S: AsRef<str>>(requirements: &[S]) -> Vec<String> {
+    let mut result: Vec<String> = requirements
+        .iter()
+        .flat_map(|r| r.as_ref().split([',', ' ']))
+        .filter(|r| !r.is_empty())
+        .map(String::from)
+        .collect();
+    result.sort();
+    result.dedup();
+    result
+}
+
+fn sorted<T: Clone + Ord>(items: &[T]) -> Vec<T> {
+    let mut items = items.to_vec();
+    items.sort();
+    items
+}
+
+fn list<T: Display>(items: &[T]) -> String {
+    if items.is_empty() {
+        return String::from("(None)");
+    }
+
+    items.iter().map(|i| i.to_string()).collect::<Vec<String>>().join(", ")
+}
+
+impl UpdateManifest {
+    pub fn load(path: &Path) -> Result<UpdateManifest, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| format!("Unable to read manifest from {}: {}", path.display(), error))?;
+
+        let manifest: UpdateManifest = toml::from_str(&contents)
+            .map_err(|error| format!("Unable to parse manifest from {}: {}", path.display(), error))?;
+
+        if manifest.builds.is_empty() {
+            return Err(String::from("The manifest does not contain any builds."));
+        }
+
+        Ok(manifest)
+    }
+
+    /// This method returns requirements in the format that is expected by the server.
+    pub fn requirements_string(&self) -> Option<String> {
+        self.requirements
+            .as_ref()
+            .map(|requirements| normalize_requirements(requirements).join(","))
+    }
+
+    /// This method returns the list of fields that would be changed by applying this manifest to
+    /// an existing update, or all fields that are set in the manifest if there is no update yet.
+    pub fn changes(&self, update: Option<&Update>) -> Vec<Change> {
+        let mut changes = Vec::new();
+
+        let mut compare = |field: &'static str, old: Option<String>, new: Option<String>| {
+            if let Some(new) = new {
+                match old {
+                    Some(old) if old == new => {},
+                    old => changes.push(Change { field, old, new }),
+                }
+            }
+        };
+
+        let builds: Option<Vec<String>> =
+            update.map(|u| sorted(&u.builds.iter().map(|b| b.nvr.clone()).collect::<Vec<_>>()));
+        compare("builds", builds.map(|b| list(&b)), Some(list(&sorted(&self.builds))));
+
+        compare("notes", update.map(|u| u.notes.clone()), Some(self.notes.clone()));
+
+        compare(
+            "autokarma",
+            update.map(|u| u.autokarma.to_string()),
+            self.autokarma.map(|v| v.to_string()),
+        );
+        compare(
+            "autotime",
+            update.map(|u| u.autotime.to_string()),
+            self.autotime.map(|v| v.to_string()),
+        );
+
+        let bugs: Option<Vec<u32>> = update.map(|u| sorted(&u.bugs.iter().map(|b| b.bug_id).collect::<Vec<_>>()));
+        compare(
+            "bugs",
+            bugs.map(|b| list(&b)),
+            self.bugs.as_ref().map(|b| list(&sorted(b))),
+        );
+
+        compare(
+            "close_bugs",
+            update.map(|u| u.close_bugs.to_string()),
+            self.close_bugs.map(|v| v.to_string()),
+        );
+        compare(
+            "display_name",
+            update.map(|u| u.display_name.clone()),
+            self.display_name.clone(),
+        );
+        compare(
+            "require_bugs",
+            update.map(|u| u.require_bugs.to_string()),
+            self.require_bugs.map(|v| v.to_string()),
+        );
+        compare(
+            "require_testcases",
+            update.map(|u| u.require_testcases.to_string()),
+            self.require_testcases.map(|v| v.to_string()),
+        );
+
+        let requirements: Option<Vec<String>> =
+            update.map(|u| normalize_requirements(&[u.requirements.as_deref().unwrap_or_default()]));
+        compare(
+            "requirements",
+            requirements.map(|r| list(&r)),
+            self.requirements.as_ref().map(|r| list(&normalize_requirements(r))),
+        );
+
+        compare(
+            "severity",
+            update.map(|u| u.severity.to_string()),
+            self.severity.map(|v| v.to_string()),
+        );
+        compare(
+            "stable_days",
+            update.map(|u| u.stable_days.map_or(String::from("(None)"), |v| v.to_string())),
+            self.stable_days.map(|v| v.to_string()),
+        );
+        compare(
+            "stable_karma",
+            update.map(|u| u.stable_karma.map_or(String::from("(None)"), |v| v.to_string())),
+            self.stable_karma.map(|v| v.to_string()),
+        );
+        compare(
+            "suggestion",
+            update.map(|u| u.suggest.to_string()),
+            self.suggestion.map(|v| v.to_string()),
+        );
+        compare(
+            "unstable_karma",
+            update.map(|u| u.unstable_karma.map_or(String::from("(None)"), |v| v.to_string())),
+            self.unstable_karma.map(|v| v.to_string()),
+        );
+        compare(
+            "type",
+            update.map(|u| u.update_type.to_string()),
+            self.update_type.map(|v| v.to_string()),
+        );
+
+        changes
+    }
+}
+
+// expiration dates are computed from a duration in days when editing overrides, so they are only
+// compared by day; otherwise, the expiration date would be different on every run
+fn same_day(a: &BodhiDate, b: &BodhiDate) -> bool {
+    match (parse_date(a), parse_date(b)) {
+        (Some(a), Some(b)) => a.date_naive() == b.date_naive(),
+        _ => a == b,
+    }
+}
+
+/// This function returns the changes that editing an override with the given values would make.
+pub fn override_changes(over_ride: &Override, notes: &str, expiration_date: &BodhiDate) -> Vec<Change> {
+    let mut changes = Vec::new();
+
+    if over_ride.notes != notes {
+        changes.push(Change {
+            field: "notes",
+            old: Some(over_ride.notes.clone()),
+            new: notes.to_owned(),
+        });
+    }
+
+    if !same_day(&over_ride.expiration_date, expiration_date) {
+        changes.push(Change {
+            field: "expiration_date",
+            old: Some(over_ride.expiration_date.to_string()),
+            new: expiration_date.to_string(),
+        });
+    }
+
+    changes
+}
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use zbus::zvariant::Value;
+use zbus::Connection;
+
+const APP_NAME: &str = "bodhi-cli";
+
+// default expiration timeout, as chosen by the notification server
+const EXPIRE_DEFAULT: i32 = -1;
+
+/// client for sending freedesktop desktop notifications over the session D-Bus
+pub struct Notifier {
+    connection: Connection,
+}
+
+impl Notifier {
+    /// This method connects to the session D-Bus. Failing to connect is not fatal, notifications
+    /// are only disabled in that case.
+    pub async fn connect() -> Option<Self> {
+        match Connection::session().await {
+            Ok(connection) => Some(Notifier { connection }),
+            Err(error) => {
+                println!(
+                    "Failed to connect to session D-Bus, notifications are disabled: {}",
+                    error
+                );
+                None
+            },
+        }
+    }
+
+    /// This method sends a desktop notification. Errors are printed, but are not fatal.
+    pub async fn send(&self, summary: &str, body: &str) {
+        let hints: HashMap<&str, Value> = HashMap::new();
+
+        let result = self
+            .connection
+            .call_method(
+                Some("org.freedesktop.Notifications"),
+                "/org/freedesktop/Notifications",
+                Some("org.freedesktop.Notifications"),
+                "Notify",
+                &(
+                    APP_NAME,
+                    0u32,
+                    "",
+                    summary,
+                    body,
+                    Vec::<&str>::new(),
+                    hints,
+                    EXPIRE_DEFAULT,
+                ),
+            )
+            .await;
+
+        if let Err(error) = result {
+            println!("Failed to send desktop notification: {}", error);
+        }
+    }
+}
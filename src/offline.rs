@@ -0,0 +1,122 @@
+use bodhi::*;
+
+/// This function determines the package name from the NVR of a build.
+fn package_name(nvr: &str) -> &str {
+    let mut parts = nvr.rsplitn(3, '-');
+    let _release = parts.next();
+    let _version = parts.next();
+
+    parts.next().unwrap_or(nvr)
+}
+
+fn since(date: &Option<BodhiDate>, since: Option<&BodhiDate>) -> bool {
+    match (date, since) {
+        (_, None) => true,
+        (Some(date), Some(since)) => date >= since,
+        (None, Some(_)) => false,
+    }
+}
+
+fn before(date: &Option<BodhiDate>, before: Option<&BodhiDate>) -> bool {
+    match (date, before) {
+        (_, None) => true,
+        (Some(date), Some(before)) => date < before,
+        (None, Some(_)) => false,
+    }
+}
+
+fn equals<T: PartialEq>(value: T, filter: Option<T>) -> bool {
+    match filter {
+        Some(filter) => value == filter,
+        None => true,
+    }
+}
+
+/// filters of the `query-updates` subcommand, applied to locally stored updates
+#[derive(Debug, Default)]
+pub struct UpdateFilter<'a> {
+    pub alias: Option<&'a str>,
+    pub bugs: Option<&'a [u32]>,
+    pub builds: Option<&'a [String]>,
+    pub critpath: Option<bool>,
+    pub content_type: Option<ContentType>,
+    pub locked: Option<bool>,
+    pub modified_before: Option<&'a BodhiDate>,
+    pub modified_since: Option<&'a BodhiDate>,
+    pub packages: Option<&'a [String]>,
+    pub pushed: Option<bool>,
+    pub pushed_before: Option<&'a BodhiDate>,
+    pub pushed_since: Option<&'a BodhiDate>,
+    pub releases: Option<&'a [FedoraRelease]>,
+    pub request: Option<UpdateRequest>,
+    pub severity: Option<UpdateSeverity>,
+    pub status: Option<UpdateStatus>,
+    pub submitted_before: Option<&'a BodhiDate>,
+    pub submitted_since: Option<&'a BodhiDate>,
+    pub suggestion: Option<UpdateSuggestion>,
+    pub update_type: Option<UpdateType>,
+    pub users: Option<&'a [String]>,
+}
+
+impl<'a> UpdateFilter<'a> {
+    pub fn matches(&self, update: &Update) -> bool {
+        equals(update.alias.as_str(), self.alias)
+            && self
+                .bugs
+                .map_or(true, |bugs| update.bugs.iter().any(|bug| bugs.contains(&bug.bug_id)))
+            && self.builds.map_or(true, |builds| {
+                update.builds.iter().any(|build| builds.contains(&build.nvr))
+            })
+            && equals(update.critpath, self.critpath)
+            && equals(update.content_type, self.content_type.map(Some))
+            && equals(update.locked, self.locked)
+            && before(&update.date_modified, self.modified_before)
+            && since(&update.date_modified, self.modified_since)
+            && self.packages.map_or(true, |packages| {
+                update
+                    .builds
+                    .iter()
+                    .any(|build| packages.iter().any(|p| p == package_name(&build.nvr)))
+            })
+            && equals(update.pushed, self.pushed)
+            && before(&update.date_pushed, self.pushed_before)
+            && since(&update.date_pushed, self.pushed_since)
+            && self
+                .releases
+                .map_or(true, |releases| releases.contains(&update.release.name))
+            && equals(update.request, self.request.map(Some))
+            && equals(update.severity, self.severity)
+            && equals(update.status, self.status)
+            && before(&update.date_submitted, self.submitted_before)
+            && since(&update.date_submitted, self.submitted_since)
+            && equals(update.suggest, self.suggestion)
+            && equals(update.update_type, self.update_type)
+            && self.users.map_or(true, |users| users.contains(&update.user.name))
+    }
+}
+
+/// filters of the `query-overrides` subcommand, applied to locally stored overrides
+#[derive(Debug, Default)]
+pub struct OverrideFilter<'a> {
+    pub builds: Option<&'a [String]>,
+    pub expired: Option<bool>,
+    pub releases: Option<&'a [FedoraRelease]>,
+    pub users: Option<&'a [String]>,
+}
+
+impl<'a> OverrideFilter<'a> {
+    /// This method checks whether an override matches the filters. Overrides only reference the
+    /// numeric ID of their release, so the name of the release (as stored when synchronizing) needs
+    /// to be supplied.
+    pub fn matches(&self, over_ride: &Override, release: Option<&str>) -> bool {
+        self.builds.map_or(true, |builds| builds.contains(&over_ride.nvr))
+            && equals(over_ride.expired_date.is_some(), self.expired)
+            && self.releases.map_or(true, |filters| match release {
+                Some(release) => filters.iter().any(|filter| filter.to_string() == release),
+                None => false,
+            })
+            && self
+                .users
+                .map_or(true, |users| users.contains(&over_ride.submitter.name))
+    }
+}
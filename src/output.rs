@@ -6,28 +6,27 @@ use serde::Serialize;
 
 use crate::cli::Format;
 
-pub fn progress_bar(p: u32, ps: u32) {
-    let columns: u32 = match terminal_size::terminal_size() {
-        Some((terminal_size::Width(w), _)) => w as u32,
-        None => return,
-    };
-
-    let width: u32 = columns - 11;
-
-    let progress = ((p as f64) / (ps as f64) * (width as f64)) as u32;
-    let remaining = width - progress;
+pub fn progress_pages(p: u32, cached: bool) {
+    if terminal_size::terminal_size().is_none() {
+        return;
+    }
 
     let line = format!(
-        " [ {}{} ] {:>3}% ",
-        "=".repeat(progress as usize),
-        " ".repeat(remaining as usize),
-        ((p as f64) / (ps as f64) * 100f64) as u32,
+        " [ page {:>5} ] {:<24}",
+        p,
+        if cached { "(loaded from checkpoint)" } else { "" },
     );
 
     print!("\r{}", &line);
     std::io::stdout().flush().expect("Failed to flush stdio.");
 }
 
+pub fn progress_finish() {
+    if terminal_size::terminal_size().is_some() {
+        println!();
+    }
+}
+
 pub fn print_server_msgs(caveats: &[HashMap<String, String>]) {
     if !caveats.is_empty() {
         println!("Server messages:");
@@ -81,3 +80,28 @@ where
 
     Ok(())
 }
+
+/// This function prints the selected fields of each item on a separate line, separated by tabs.
+/// Nested fields can be selected with dots (i.e. "release.name"), missing or null values are
+/// printed as empty strings, and strings are printed without quotes.
+pub fn field_outputs<T>(input: &[T], fields: &[String]) -> Result<(), String>
+where
+    T: Serialize,
+{
+    for item in input {
+        let value = serde_json::to_value(item).map_err(|error| error.to_string())?;
+
+        let values: Vec<String> = fields
+            .iter()
+            .map(|field| match value.pointer(&format!("/{}", field.replace('.', "/"))) {
+                None | Some(serde_json::Value::Null) => String::new(),
+                Some(serde_json::Value::String(string)) => string.clone(),
+                Some(other) => other.to_string(),
+            })
+            .collect();
+
+        println!("{}", values.join("\t"));
+    }
+
+    Ok(())
+}
@@ -1,19 +1,73 @@
 use bodhi::BodhiClient;
 use bodhi::Override;
 use bodhi::OverrideNVRQuery;
+use bodhi::OverridePageQuery;
+use bodhi::OverrideQuery;
 use bodhi::Update;
 use bodhi::UpdateIDQuery;
+use bodhi::UpdatePageQuery;
+use bodhi::UpdateQuery;
+use bodhi::UpdateStatus;
 
-pub async fn query_override(bodhi: &BodhiClient, nvr: &str) -> Result<Override, String> {
-    bodhi
-        .request(&OverrideNVRQuery::new(nvr))
+use crate::record::{Recorder, RequestKey};
+
+pub async fn query_override(bodhi: &BodhiClient, recorder: &Recorder, nvr: &str) -> Result<Override, String> {
+    recorder
+        .request(
+            &RequestKey::get(format!("/overrides/{}", nvr)),
+            bodhi.request(&OverrideNVRQuery::new(nvr)),
+        )
+        .await
+}
+
+pub async fn query_update(bodhi: &BodhiClient, recorder: &Recorder, alias: &str) -> Result<Update, String> {
+    recorder
+        .request(
+            &RequestKey::get(format!("/updates/{}", alias)),
+            bodhi.request(&UpdateIDQuery::new(alias)),
+        )
         .await
-        .map_err(|error| error.to_string())
 }
 
-pub async fn query_update(bodhi: &BodhiClient, alias: &str) -> Result<Update, String> {
-    bodhi
-        .request(&UpdateIDQuery::new(alias))
+/// This function returns all updates of the given user that have one of the given states.
+pub async fn query_user_updates(
+    bodhi: &BodhiClient,
+    recorder: &Recorder,
+    username: &str,
+    statuses: &[UpdateStatus],
+) -> Result<Vec<Update>, String> {
+    let users = [username];
+    let mut updates = Vec::new();
+
+    for status in statuses {
+        let query = UpdateQuery::new().users(&users).status(*status);
+
+        let result: Vec<Update> = recorder
+            .request(
+                &RequestKey::query("/updates/", &UpdatePageQuery::from_query(&query, 0))?,
+                bodhi.paginated_request(&query),
+            )
+            .await?;
+
+        updates.extend(result);
+    }
+
+    Ok(updates)
+}
+
+/// This function returns all overrides of the given user that have not expired yet.
+pub async fn query_user_overrides(
+    bodhi: &BodhiClient,
+    recorder: &Recorder,
+    username: &str,
+) -> Result<Vec<Override>, String> {
+    let users = [username];
+    let query = OverrideQuery::new().users(&users).expired(false);
+
+    recorder
+        .request(
+            &RequestKey::query("/overrides/", &OverridePageQuery::from_query(&query, 0))?,
+            bodhi.paginated_request(&query),
+        )
         .await
-        .map_err(|error| error.to_string())
 }
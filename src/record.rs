@@ -0,0 +1,337 @@
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bodhi::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::dry_run::DryRun;
+
+/// conversion between bodhi response types and their JSON representation in recordings
+///
+/// Most types returned by bodhi requests can be converted with serde directly. Types that are
+/// returned from creating or editing data on the server only implement `Deserialize`, so they are
+/// converted to JSON manually, in the same format as they were returned by the server.
+pub trait Recordable: Sized {
+    fn to_value(&self) -> Result<Value, String>;
+    fn from_value(value: Value) -> Result<Self, String>;
+}
+
+macro_rules! recordable {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl Recordable for $t {
+                fn to_value(&self) -> Result<Value, String> {
+                    serde_json::to_value(self).map_err(|error| error.to_string())
+                }
+
+                fn from_value(value: Value) -> Result<Self, String> {
+                    serde_json::from_value(value).map_err(|error| error.to_string())
+                }
+            }
+        )*
+    };
+}
+
+// types which contain a flattened object and a list of server messages
+macro_rules! recordable_with_caveats {
+    ($($t:ty => $field:ident),* $(,)?) => {
+        $(
+            impl Recordable for $t {
+                fn to_value(&self) -> Result<Value, String> {
+                    let mut value = serde_json::to_value(&self.$field).map_err(|error| error.to_string())?;
+                    let caveats = serde_json::to_value(&self.caveats).map_err(|error| error.to_string())?;
+
+                    match value.as_object_mut() {
+                        Some(object) => object.insert(String::from("caveats"), caveats),
+                        None => return Err(String::from("Failed to record server response.")),
+                    };
+
+                    Ok(value)
+                }
+
+                fn from_value(value: Value) -> Result<Self, String> {
+                    serde_json::from_value(value).map_err(|error| error.to_string())
+                }
+            }
+        )*
+    };
+}
+
+recordable!(
+    Build,
+    Comment,
+    Compose,
+    Override,
+    Package,
+    Release,
+    Update,
+    User,
+    Vec<Build>,
+    Vec<Comment>,
+    Vec<Compose>,
+    Vec<Override>,
+    Vec<Package>,
+    Vec<Release>,
+    Vec<Update>,
+    Vec<User>,
+);
+
+recordable_with_caveats!(
+    EditedOverride => over_ride,
+    EditedUpdate => update,
+    NewOverride => over_ride,
+    NewUpdate => update,
+);
+
+impl Recordable for NewComment {
+    fn to_value(&self) -> Result<Value, String> {
+        Ok(serde_json::json!({
+            "comment": serde_json::to_value(&self.comment).map_err(|error| error.to_string())?,
+            "caveats": serde_json::to_value(&self.caveats).map_err(|error| error.to_string())?,
+        }))
+    }
+
+    fn from_value(value: Value) -> Result<Self, String> {
+        serde_json::from_value(value).map_err(|error| error.to_string())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Outcome {
+    Response(Value),
+    NotFound,
+    Error(String),
+}
+
+/// method, path, and parameters of a request to the bodhi server, which identify the request in
+/// recordings
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RequestKey {
+    method: String,
+    path: String,
+    params: Value,
+}
+
+impl RequestKey {
+    /// This method returns the key for a GET request without parameters.
+    pub fn get(path: impl Into<String>) -> Self {
+        RequestKey {
+            method: String::from("GET"),
+            path: path.into(),
+            params: Value::Null,
+        }
+    }
+
+    /// This method returns the key for a GET request with the parameters of the given page query.
+    /// Requests for all pages of a query use the page query for page 0. Unset parameters are not
+    /// sent to the server, so they are left out.
+    pub fn query<Q: Serialize>(path: impl Into<String>, query: &Q) -> Result<Self, String> {
+        let mut params = serde_json::to_value(query).map_err(|_| String::from("Failed to serialize query."))?;
+
+        if let Some(object) = params.as_object_mut() {
+            object.retain(|_, value| !value.is_null());
+        }
+
+        Ok(RequestKey {
+            method: String::from("GET"),
+            path: path.into(),
+            params,
+        })
+    }
+
+    /// This method returns the key for a request with the given JSON body, as it is sent to the
+    /// server. The CSRF token is left out, and dates are rounded to the day, because they are
+    /// computed from the current time.
+    pub fn post(method: &str, path: &str, body: &str) -> Self {
+        let mut params = serde_json::from_str(body).unwrap_or_else(|_| Value::String(body.to_owned()));
+
+        if let Some(object) = params.as_object_mut() {
+            object.remove("csrf_token");
+
+            if let Some(Value::String(date)) = object.get_mut("expiration_date") {
+                date.truncate("YYYY-MM-DD".len());
+            }
+        }
+
+        RequestKey {
+            method: method.to_owned(),
+            path: path.to_owned(),
+            params,
+        }
+    }
+}
+
+impl Display for RequestKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.params {
+            Value::Null => write!(f, "{} {}", &self.method, &self.path),
+            params => write!(f, "{} {} {}", &self.method, &self.path, params),
+        }
+    }
+}
+
+/// single recorded request, stored as one JSON file per request in the recording directory
+#[derive(Debug, Deserialize, Serialize)]
+struct Exchange {
+    request: RequestKey,
+    outcome: Outcome,
+}
+
+#[derive(Debug)]
+enum Mode {
+    Live,
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+/// wrapper for requests made with a [`BodhiClient`] that can record requests and their responses
+/// to a directory, or replay previously recorded responses without contacting the server
+///
+/// Every request is recorded with its [`RequestKey`]. Responses are recorded after they have been
+/// parsed by the bodhi crate, so neither credentials nor session cookies are ever written to disk.
+/// The keys of requests that modify data on the server are based on their payload, which is
+/// captured with a [`DryRun`] server.
+pub struct Recorder {
+    mode: Mode,
+    counter: AtomicUsize,
+    capture: Option<DryRun>,
+}
+
+impl Recorder {
+    pub fn live() -> Self {
+        Recorder {
+            mode: Mode::Live,
+            counter: AtomicUsize::new(0),
+            capture: None,
+        }
+    }
+
+    pub async fn record(dir: PathBuf, server_url: &str) -> Result<Self, String> {
+        std::fs::create_dir_all(&dir).map_err(|error| format!("Failed to create recording directory: {}", error))?;
+
+        // requests are numbered, so files from a previous recording would be mixed with new ones
+        let mut entries =
+            std::fs::read_dir(&dir).map_err(|error| format!("Failed to read recording directory: {}", error))?;
+        if entries.next().is_some() {
+            return Err(format!("Recording directory is not empty: {}", dir.display()));
+        }
+
+        Ok(Recorder {
+            mode: Mode::Record(dir),
+            counter: AtomicUsize::new(0),
+            capture: Some(DryRun::start(server_url).await?),
+        })
+    }
+
+    pub async fn replay(dir: PathBuf, server_url: &str) -> Result<Self, String> {
+        if !dir.is_dir() {
+            return Err(format!("Recording directory does not exist: {}", dir.display()));
+        }
+
+        Ok(Recorder {
+            mode: Mode::Replay(dir),
+            counter: AtomicUsize::new(0),
+            capture: Some(DryRun::start(server_url).await?),
+        })
+    }
+
+    /// This method returns `true` if responses are replayed instead of requested from the server.
+    pub fn replaying(&self) -> bool {
+        matches!(self.mode, Mode::Replay(_))
+    }
+
+    /// This method runs a request, and records or replays its result, depending on the mode. The
+    /// `request` key needs to describe the request that is made by the `response` future. When
+    /// replaying, the `response` future is never polled, and the recorded request needs to have the
+    /// same key.
+    pub async fn request<T, F>(&self, request: &RequestKey, response: F) -> Result<T, String>
+    where
+        T: Recordable,
+        F: Future<Output = Result<T, QueryError>>,
+    {
+        match self.optional(request, response).await? {
+            Some(result) => Ok(result),
+            None => Err(QueryError::NotFound.to_string()),
+        }
+    }
+
+    /// This method runs a request that modifies data on the server, and records or replays its
+    /// result, like [`Recorder::request`]. The `capture` function needs to make the same request as
+    /// the `response` future with the given client, so its payload can be used as the request key.
+    pub async fn post<'r, T, C, G, F>(&'r self, capture: C, response: F) -> Result<T, String>
+    where
+        T: Recordable,
+        C: FnOnce(&'r BodhiClient) -> G,
+        G: Future<Output = Result<T, QueryError>>,
+        F: Future<Output = Result<T, QueryError>>,
+    {
+        let request = match &self.capture {
+            Some(dry_run) => dry_run.request_key(capture(dry_run.client())).await?,
+            None => return response.await.map_err(|error| error.to_string()),
+        };
+
+        self.request(&request, response).await
+    }
+
+    /// This method works like [`Recorder::request`], but returns `None` instead of an error if the
+    /// requested object does not exist on the server.
+    pub async fn optional<T, F>(&self, request: &RequestKey, response: F) -> Result<Option<T>, String>
+    where
+        T: Recordable,
+        F: Future<Output = Result<T, QueryError>>,
+    {
+        let number = self.counter.fetch_add(1, Ordering::SeqCst) + 1;
+
+        match &self.mode {
+            Mode::Live => match response.await {
+                Ok(result) => Ok(Some(result)),
+                Err(QueryError::NotFound) => Ok(None),
+                Err(error) => Err(error.to_string()),
+            },
+            Mode::Record(dir) => {
+                let (outcome, result) = match response.await {
+                    Ok(result) => (Outcome::Response(result.to_value()?), Ok(Some(result))),
+                    Err(QueryError::NotFound) => (Outcome::NotFound, Ok(None)),
+                    Err(error) => (Outcome::Error(error.to_string()), Err(error.to_string())),
+                };
+
+                let exchange = Exchange {
+                    request: request.clone(),
+                    outcome,
+                };
+
+                let contents = serde_json::to_string_pretty(&exchange)
+                    .map_err(|_| String::from("Failed to serialize recorded request."))?;
+                std::fs::write(dir.join(format!("{:04}.json", number)), contents)
+                    .map_err(|error| format!("Failed to write recorded request: {}", error))?;
+
+                result
+            },
+            Mode::Replay(dir) => {
+                let path = dir.join(format!("{:04}.json", number));
+
+                let contents = std::fs::read_to_string(&path)
+                    .map_err(|_| format!("No recorded response for request #{} ({}).", number, request))?;
+                let exchange: Exchange = serde_json::from_str(&contents)
+                    .map_err(|_| format!("Failed to parse recorded request: {}", path.display()))?;
+
+                if &exchange.request != request {
+                    return Err(format!(
+                        "Recorded request #{} does not match: expected '{}', found '{}'.",
+                        number, request, exchange.request
+                    ));
+                }
+
+                match exchange.outcome {
+                    Outcome::Response(value) => Ok(Some(T::from_value(value)?)),
+                    Outcome::NotFound => Ok(None),
+                    Outcome::Error(error) => Err(error),
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,604 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+use bodhi::{Comment, Compose, Override, Release, Update};
+use rusqlite::types::ValueRef;
+use rusqlite::{params, Connection, OptionalExtension, Params};
+use serde::de::DeserializeOwned;
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+
+use crate::config::cache_dir;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS updates (
+    alias TEXT PRIMARY KEY,
+    update_id INTEGER,
+    title TEXT NOT NULL,
+    release TEXT NOT NULL,
+    status TEXT NOT NULL,
+    request TEXT,
+    update_type TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    submitter TEXT NOT NULL,
+    karma INTEGER,
+    date_submitted TEXT,
+    date_modified TEXT,
+    date_pushed TEXT,
+    date_stable TEXT,
+    data TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS builds (
+    nvr TEXT PRIMARY KEY,
+    update_alias TEXT NOT NULL REFERENCES updates (alias) ON DELETE CASCADE,
+    build_type TEXT NOT NULL,
+    signed INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS bugs (
+    bug_id INTEGER NOT NULL,
+    update_alias TEXT NOT NULL REFERENCES updates (alias) ON DELETE CASCADE,
+    title TEXT,
+    security INTEGER NOT NULL,
+    PRIMARY KEY (bug_id, update_alias)
+);
+
+CREATE TABLE IF NOT EXISTS comments (
+    id INTEGER PRIMARY KEY,
+    update_id INTEGER NOT NULL,
+    update_alias TEXT,
+    user TEXT NOT NULL,
+    karma INTEGER NOT NULL,
+    timestamp TEXT NOT NULL,
+    text TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS overrides (
+    nvr TEXT PRIMARY KEY,
+    release TEXT,
+    submitter TEXT NOT NULL,
+    notes TEXT NOT NULL,
+    submission_date TEXT NOT NULL,
+    expiration_date TEXT NOT NULL,
+    expired_date TEXT,
+    data TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS releases (
+    name TEXT PRIMARY KEY,
+    long_name TEXT NOT NULL,
+    state TEXT NOT NULL,
+    branch TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS composes (
+    release TEXT NOT NULL,
+    request TEXT NOT NULL,
+    state TEXT NOT NULL,
+    date_created TEXT NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (release, request)
+);
+
+CREATE TABLE IF NOT EXISTS sync_state (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+CREATE VIEW IF NOT EXISTS active_overrides AS
+    SELECT nvr, submitter, notes, submission_date, expiration_date
+    FROM overrides
+    WHERE expired_date IS NULL;
+
+CREATE VIEW IF NOT EXISTS update_karma AS
+    SELECT
+        updates.alias,
+        updates.release,
+        updates.status,
+        updates.karma,
+        json_extract(updates.data, '$.stable_karma') AS stable_karma,
+        json_extract(updates.data, '$.unstable_karma') AS unstable_karma,
+        COUNT(CASE WHEN comments.karma > 0 THEN 1 END) AS positive,
+        COUNT(CASE WHEN comments.karma < 0 THEN 1 END) AS negative
+    FROM updates
+    LEFT JOIN comments ON comments.update_alias = updates.alias
+    GROUP BY updates.alias;
+
+CREATE VIEW IF NOT EXISTS bug_feedback AS
+    SELECT
+        comments.id AS comment_id,
+        comments.update_alias,
+        comments.user,
+        json_extract(feedback.value, '$.bug_id') AS bug_id,
+        json_extract(feedback.value, '$.karma') AS karma
+    FROM comments, json_each(comments.data, '$.bug_feedback') AS feedback;
+
+CREATE VIEW IF NOT EXISTS testcase_feedback AS
+    SELECT
+        comments.id AS comment_id,
+        comments.update_alias,
+        comments.user,
+        json_extract(feedback.value, '$.testcase.name') AS testcase,
+        json_extract(feedback.value, '$.karma') AS karma
+    FROM comments, json_each(comments.data, '$.testcase_feedback') AS feedback;
+";
+
+/// This function returns the host name of the bodhi instance at the given URL, in a form that
+/// can be used as part of a file name.
+pub fn server_host(server_url: &str) -> String {
+    server_url
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/')
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// This function returns the path of the local database for the bodhi instance at the given URL.
+pub fn store_path(server_url: &str) -> Result<PathBuf, String> {
+    Ok(cache_dir()?.join(format!("{}.sqlite", server_host(server_url))))
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|_| String::from("Failed to serialize data for local database."))
+}
+
+fn db_error(error: rusqlite::Error) -> String {
+    format!("Local database error: {}", error)
+}
+
+fn from_json<T: DeserializeOwned>(value: &str) -> Result<T, String> {
+    serde_json::from_str(value).map_err(|_| String::from("Failed to deserialize data from local database."))
+}
+
+/// single result row of an ad-hoc SQL query, with values in the order of the selected columns
+#[derive(Debug)]
+pub struct SqlRow {
+    columns: Vec<(String, serde_json::Value)>,
+}
+
+impl Serialize for SqlRow {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.columns.len()))?;
+        for (name, value) in &self.columns {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+impl Display for SqlRow {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let width = self.columns.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+
+        for (name, value) in &self.columns {
+            let value = match value {
+                serde_json::Value::Null => String::from("(None)"),
+                serde_json::Value::String(string) => string.to_owned(),
+                other => other.to_string(),
+            };
+
+            writeln!(f, "{:<width$} {}", format!("{}:", name), value, width = width + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn sql_value(value: ValueRef) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(integer) => integer.into(),
+        ValueRef::Real(real) => real.into(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).into(),
+        ValueRef::Blob(blob) => format!("(binary data, {} bytes)", blob.len()).into(),
+    }
+}
+
+/// local SQLite mirror of bodhi data, as maintained by the `sync` subcommand
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    /// This method opens (and, if necessary, initializes) the local database for the given server.
+    pub fn open(server_url: &str) -> Result<Store, String> {
+        let path = store_path(server_url)?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|error| format!("Failed to create directory for local database: {}", error))?;
+        }
+
+        let conn = Connection::open(&path).map_err(db_error)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;").map_err(db_error)?;
+        conn.execute_batch(SCHEMA).map_err(db_error)?;
+
+        Ok(Store { conn })
+    }
+
+    /// This method runs the given function inside a database transaction. Changes are only
+    /// committed if the function returns successfully.
+    pub fn transaction<T>(&self, f: impl FnOnce(&Store) -> Result<T, String>) -> Result<T, String> {
+        let tx = self.conn.unchecked_transaction().map_err(db_error)?;
+        let result = f(self)?;
+        tx.commit().map_err(db_error)?;
+        Ok(result)
+    }
+
+    pub fn get_state(&self, key: &str) -> Result<Option<String>, String> {
+        self.conn
+            .query_row("SELECT value FROM sync_state WHERE key = ?1", params![key], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(db_error)
+    }
+
+    pub fn set_state(&self, key: &str, value: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO sync_state (key, value) VALUES (?1, ?2)",
+                params![key, value],
+            )
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// This method inserts or replaces an update, together with its builds, bugs, and comments.
+    pub fn insert_update(&self, update: &Update) -> Result<(), String> {
+        let update_id = update
+            .comments
+            .as_ref()
+            .and_then(|comments| comments.first())
+            .map(|comment| comment.update_id);
+
+        self.conn
+            .execute("DELETE FROM updates WHERE alias = ?1", params![&update.alias])
+            .map_err(db_error)?;
+
+        self.conn
+            .execute(
+                "INSERT INTO updates (
+                    alias, update_id, title, release, status, request, update_type, severity, submitter, karma,
+                    date_submitted, date_modified, date_pushed, date_stable, data
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+                params![
+                    &update.alias,
+                    update_id,
+                    &update.title,
+                    update.release.name.to_string(),
+                    update.status.to_string(),
+                    update.request.map(|request| request.to_string()),
+                    update.update_type.to_string(),
+                    update.severity.to_string(),
+                    &update.user.name,
+                    update.karma,
+                    update.date_submitted.as_ref().map(|date| date.to_string()),
+                    update.date_modified.as_ref().map(|date| date.to_string()),
+                    update.date_pushed.as_ref().map(|date| date.to_string()),
+                    update.date_stable.as_ref().map(|date| date.to_string()),
+                    to_json(update)?,
+                ],
+            )
+            .map_err(db_error)?;
+
+        for build in &update.builds {
+            self.conn
+                .execute(
+                    "INSERT OR REPLACE INTO builds (nvr, update_alias, build_type, signed) VALUES (?1, ?2, ?3, ?4)",
+                    params![&build.nvr, &update.alias, build.build_type.to_string(), build.signed],
+                )
+                .map_err(db_error)?;
+        }
+
+        for bug in &update.bugs {
+            self.conn
+                .execute(
+                    "INSERT OR REPLACE INTO bugs (bug_id, update_alias, title, security) VALUES (?1, ?2, ?3, ?4)",
+                    params![bug.bug_id, &update.alias, &bug.title, bug.security],
+                )
+                .map_err(db_error)?;
+        }
+
+        if let Some(comments) = &update.comments {
+            for comment in comments {
+                self.insert_comment(comment, Some(&update.alias))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// This method inserts or replaces a comment. If the alias of the update it belongs to is not
+    /// known, it is looked up from already stored updates.
+    pub fn insert_comment(&self, comment: &Comment, alias: Option<&str>) -> Result<(), String> {
+        let alias = alias.or(comment.update.as_ref().map(|update| update.alias.as_str()));
+
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO comments (id, update_id, update_alias, user, karma, timestamp, text, data)
+                VALUES (?1, ?2, COALESCE(?3, (SELECT alias FROM updates WHERE update_id = ?2)), ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    comment.id,
+                    comment.update_id,
+                    alias,
+                    &comment.user.name,
+                    comment.karma as i8,
+                    comment.timestamp.to_string(),
+                    &comment.text,
+                    to_json(comment)?,
+                ],
+            )
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// This method inserts or replaces an override. Overrides only reference the numeric ID of
+    /// their release, so the release name needs to be supplied, if it is known.
+    pub fn insert_override(&self, over_ride: &Override, release: Option<&str>) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO overrides (
+                    nvr, release, submitter, notes, submission_date, expiration_date, expired_date, data
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    &over_ride.nvr,
+                    release,
+                    &over_ride.submitter.name,
+                    &over_ride.notes,
+                    over_ride.submission_date.to_string(),
+                    over_ride.expiration_date.to_string(),
+                    over_ride.expired_date.as_ref().map(|date| date.to_string()),
+                    to_json(over_ride)?,
+                ],
+            )
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    pub fn insert_release(&self, release: &Release) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO releases (name, long_name, state, branch, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    release.name.to_string(),
+                    &release.long_name,
+                    release.state.to_string(),
+                    &release.branch,
+                    to_json(release)?,
+                ],
+            )
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// This method replaces all stored composes, since they only exist while they are running.
+    pub fn replace_composes(&self, composes: &[Compose]) -> Result<(), String> {
+        self.conn.execute("DELETE FROM composes", []).map_err(db_error)?;
+
+        for compose in composes {
+            let release = match &compose.release {
+                Some(release) => release.name.to_string(),
+                None => compose.release_id.to_string(),
+            };
+
+            self.conn
+                .execute(
+                    "INSERT OR REPLACE INTO composes (release, request, state, date_created, data)
+                    VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        release,
+                        compose.request.to_string(),
+                        compose.state.to_string(),
+                        compose.date_created.to_string(),
+                        to_json(compose)?,
+                    ],
+                )
+                .map_err(db_error)?;
+        }
+
+        Ok(())
+    }
+
+    /// This method returns an error if the local database has never been synchronized.
+    pub fn ensure_synced(&self) -> Result<(), String> {
+        match self.get_state("last_sync")? {
+            Some(_) => Ok(()),
+            None => Err(String::from(
+                "No local data available. Run 'bodhi-cli sync' before using '--offline'.",
+            )),
+        }
+    }
+
+    fn load<T, P>(&self, sql: &str, params: P) -> Result<Vec<T>, String>
+    where
+        T: DeserializeOwned,
+        P: Params,
+    {
+        let mut statement = self.conn.prepare(sql).map_err(db_error)?;
+        let rows = statement
+            .query_map(params, |row| row.get::<_, String>(0))
+            .map_err(db_error)?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(from_json(&row.map_err(db_error)?)?);
+        }
+        Ok(results)
+    }
+
+    pub fn updates(&self) -> Result<Vec<Update>, String> {
+        self.load("SELECT data FROM updates ORDER BY date_submitted DESC", [])
+    }
+
+    /// This method returns all stored overrides, together with the name of their release, if it is
+    /// known.
+    pub fn overrides(&self) -> Result<Vec<(Override, Option<String>)>, String> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT data, release FROM overrides ORDER BY submission_date DESC")
+            .map_err(db_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+            })
+            .map_err(db_error)?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            let (data, release) = row.map_err(db_error)?;
+            results.push((from_json(&data)?, release));
+        }
+        Ok(results)
+    }
+
+    pub fn releases(&self) -> Result<Vec<Release>, String> {
+        self.load("SELECT data FROM releases ORDER BY name", [])
+    }
+
+    pub fn release(&self, name: &str) -> Result<Option<Release>, String> {
+        Ok(self
+            .load("SELECT data FROM releases WHERE name = ?1", params![name])?
+            .pop())
+    }
+
+    pub fn composes(&self) -> Result<Vec<Compose>, String> {
+        self.load("SELECT data FROM composes ORDER BY date_created", [])
+    }
+
+    /// This method returns the mapping from numeric release IDs to release names, as far as it can
+    /// be determined from the builds of stored updates and overrides.
+    pub fn release_ids(&self) -> Result<HashMap<u32, String>, String> {
+        let mut statement = self
+            .conn
+            .prepare(
+                "SELECT DISTINCT json_extract(build.value, '$.release_id'), updates.release
+                FROM updates, json_each(updates.data, '$.builds') AS build
+                WHERE json_extract(build.value, '$.release_id') IS NOT NULL
+                UNION
+                SELECT DISTINCT json_extract(data, '$.build.release_id'), release
+                FROM overrides
+                WHERE json_extract(data, '$.build.release_id') IS NOT NULL AND release IS NOT NULL",
+            )
+            .map_err(db_error)?;
+
+        let rows = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(db_error)?;
+        rows.collect::<Result<HashMap<u32, String>, _>>().map_err(db_error)
+    }
+
+    /// This method runs an ad-hoc SQL query. Only statements that do not modify the database are
+    /// accepted.
+    pub fn query(&self, sql: &str) -> Result<Vec<SqlRow>, String> {
+        self.conn.execute_batch("PRAGMA query_only = ON;").map_err(db_error)?;
+
+        let mut statement = self.conn.prepare(sql).map_err(db_error)?;
+
+        if !statement.readonly() {
+            return Err(String::from("Only read-only SQL statements are allowed."));
+        }
+
+        let names: Vec<String> = statement.column_names().into_iter().map(String::from).collect();
+
+        let mut rows = statement.query([]).map_err(db_error)?;
+        let mut results = Vec::new();
+
+        while let Some(row) = rows.next().map_err(db_error)? {
+            let mut columns = Vec::with_capacity(names.len());
+            for (index, name) in names.iter().enumerate() {
+                columns.push((name.to_owned(), sql_value(row.get_ref(index).map_err(db_error)?)));
+            }
+            results.push(SqlRow { columns });
+        }
+
+        Ok(results)
+    }
+
+    pub fn has_update(&self, alias: &str) -> Result<bool, String> {
+        self.conn
+            .query_row("SELECT 1 FROM updates WHERE alias = ?1", params![alias], |_| Ok(()))
+            .optional()
+            .map(|row| row.is_some())
+            .map_err(db_error)
+    }
+
+    /// This method returns the alias of the stored update with the given numeric ID, if any.
+    pub fn update_alias(&self, update_id: u32) -> Result<Option<String>, String> {
+        self.conn
+            .query_row(
+                "SELECT alias FROM updates WHERE update_id = ?1",
+                params![update_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(db_error)
+    }
+
+    /// This method returns the NVRs of all overrides that were not expired when they were stored.
+    pub fn active_overrides(&self) -> Result<Vec<String>, String> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT nvr FROM overrides WHERE expired_date IS NULL")
+            .map_err(db_error)?;
+
+        let rows = statement.query_map([], |row| row.get(0)).map_err(db_error)?;
+        rows.collect::<Result<Vec<String>, _>>().map_err(db_error)
+    }
+
+    /// This method removes all updates (with their builds and bugs), comments, and overrides. It is
+    /// used before a full synchronization, so no data from an earlier scope is left behind.
+    pub fn clear_mirror(&self) -> Result<(), String> {
+        self.conn
+            .execute_batch("DELETE FROM comments; DELETE FROM updates; DELETE FROM overrides;")
+            .map_err(db_error)
+    }
+
+    pub fn remove_override(&self, nvr: &str) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM overrides WHERE nvr = ?1", params![nvr])
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// This method returns the number of rows in each of the mirrored tables.
+    pub fn counts(&self) -> Result<Vec<(&'static str, i64)>, String> {
+        let mut counts = Vec::new();
+
+        for table in [
+            "updates",
+            "builds",
+            "bugs",
+            "comments",
+            "overrides",
+            "releases",
+            "composes",
+        ] {
+            let count: i64 = self
+                .conn
+                .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+                .map_err(db_error)?;
+            counts.push((table, count));
+        }
+
+        Ok(counts)
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::{HashMap, HashSet};
+
+use bodhi::*;
+
+use crate::checkpoint::{checkpointed_query, PAGE_ROWS};
+use crate::record::{Recorder, RequestKey};
+use crate::store::Store;
+
+// Incremental synchronization re-fetches data that changed up to one hour before the last
+// successful run, to account for clock skew and for changes that happened while it was running.
+const SYNC_OVERLAP_HOURS: i64 = 1;
+
+// Maximum number of updates that are queried by alias at once.
+const ALIAS_CHUNK_SIZE: usize = 20;
+
+async fn query_updates(
+    bodhi: &BodhiClient,
+    recorder: &Recorder,
+    query: &UpdateQuery<'_>,
+) -> Result<Vec<Update>, String> {
+    recorder
+        .request(
+            &RequestKey::query("/updates/", &UpdatePageQuery::from_query(query, 0))?,
+            bodhi.paginated_request(query),
+        )
+        .await
+}
+
+/// This function determines the names of the releases of the given overrides, by NVR.
+///
+/// Overrides only reference the numeric ID of their release, which is not part of the release data
+/// returned by the server. IDs are mapped to release names with the builds of fetched and stored
+/// updates and previously stored overrides. For releases without any of those, the ID is looked up
+/// by querying a single override of that release.
+async fn override_releases(
+    bodhi: &BodhiClient,
+    recorder: &Recorder,
+    store: &Store,
+    releases: &[&Release],
+    updates: &HashMap<String, Update>,
+    overrides: &[Override],
+) -> Result<HashMap<String, String>, String> {
+    let mut names = store.release_ids()?;
+
+    for update in updates.values() {
+        for build in &update.builds {
+            if let Some(id) = build.release_id {
+                names.insert(id, update.release.name.to_string());
+            }
+        }
+    }
+
+    for release in releases {
+        let unknown = overrides
+            .iter()
+            .filter_map(|over_ride| over_ride.build.release_id)
+            .any(|id| !names.contains_key(&id));
+        if !unknown {
+            break;
+        }
+
+        let name = release.name.to_string();
+        if names.values().any(|known| known == &name) {
+            continue;
+        }
+
+        let filter = [release.name.clone()];
+        let query = OverrideQuery::new().releases(&filter).rows_per_page(1);
+        let page_query = OverridePageQuery::from_query(&query, 1);
+
+        let result: Vec<Override> = recorder
+            .request(
+                &RequestKey::query("/overrides/", &page_query)?,
+                bodhi.request(&page_query),
+            )
+            .await?;
+
+        if let Some(id) = result.first().and_then(|over_ride| over_ride.build.release_id) {
+            names.insert(id, name);
+        }
+    }
+
+    Ok(overrides
+        .iter()
+        .filter_map(|over_ride| {
+            let name = names.get(&over_ride.build.release_id?)?;
+            Some((over_ride.nvr.clone(), name.clone()))
+        })
+        .collect())
+}
+
+/// This function updates the local database with data from the bodhi server.
+///
+/// The first run (or a run with `full` set to `true`) fetches all updates and overrides (for the
+/// given releases, if any). Later runs only fetch updates that were submitted, modified, or pushed
+/// since the last run, comments that were posted since the last run (and the updates they belong
+/// to), and overrides that are either still active or were active during the last run.
+pub async fn sync(
+    bodhi: &BodhiClient,
+    recorder: &Recorder,
+    store: &Store,
+    server_url: &str,
+    releases: Option<&[FedoraRelease]>,
+    full: bool,
+    resume: bool,
+) -> Result<(), String> {
+    let started = chrono::Utc::now();
+
+    let scope = match releases {
+        Some(releases) => releases
+            .iter()
+            .map(|r| r.to_string())
+            .collect::<Vec<String>>()
+            .join(","),
+        None => String::from("all"),
+    };
+
+    let last_sync = match (store.get_state("last_sync")?, store.get_state("scope")?) {
+        (Some(date), Some(stored)) if stored == scope && !full => Some(date),
+        _ => None,
+    };
+
+    println!("Fetching releases ...");
+    let all_releases: Vec<Release> = recorder
+        .request(
+            &RequestKey::query("/releases/", &ReleasePageQuery::from_query(&ReleaseQuery::new(), 0))?,
+            bodhi.paginated_request(&ReleaseQuery::new()),
+        )
+        .await?;
+
+    println!("Fetching running composes ...");
+    let composes: Vec<Compose> = recorder
+        .request(&RequestKey::get("/composes/"), bodhi.request(&ComposeQuery::new()))
+        .await?;
+
+    let mut updates: HashMap<String, Update> = HashMap::new();
+    let mut comments: Vec<Comment> = Vec::new();
+    let mut overrides: Vec<Override>;
+    let mut removed_overrides: Vec<String> = Vec::new();
+
+    let incremental = last_sync.is_some();
+
+    if let Some(last_sync) = last_sync {
+        let since: BodhiDate = match chrono::DateTime::parse_from_rfc3339(&last_sync) {
+            Ok(date) => (date.with_timezone(&chrono::Utc) - chrono::Duration::hours(SYNC_OVERLAP_HOURS)).into(),
+            Err(_) => return Err(String::from("Invalid timestamp of last synchronization, use '--full'.")),
+        };
+
+        println!("Fetching updates changed since {} ...", &since);
+
+        let base = || {
+            let query = UpdateQuery::new();
+            match releases {
+                Some(releases) => query.releases(releases),
+                None => query,
+            }
+        };
+
+        for query in [
+            base().modified_since(&since),
+            base().submitted_since(&since),
+            base().pushed_since(&since),
+        ] {
+            for update in query_updates(bodhi, recorder, &query).await? {
+                updates.insert(update.alias.clone(), update);
+            }
+        }
+
+        println!("Fetching comments posted since {} ...", &since);
+        let query = CommentQuery::new().since(&since);
+        comments = recorder
+            .request(
+                &RequestKey::query("/comments/", &CommentPageQuery::from_query(&query, 0))?,
+                bodhi.paginated_request(&query),
+            )
+            .await?;
+
+        // karma and feedback in new comments change their updates without touching date_modified
+        let commented: Vec<String> = comments
+            .iter()
+            .filter_map(|comment| comment.update.as_ref().map(|update| update.alias.clone()))
+            .filter(|alias| !updates.contains_key(alias))
+            .collect::<HashSet<String>>()
+            .into_iter()
+            .collect();
+
+        for chunk in commented.chunks(ALIAS_CHUNK_SIZE) {
+            let aliases: Vec<&str> = chunk.iter().map(|alias| alias.as_str()).collect();
+
+            for update in query_updates(bodhi, recorder, &base().aliases(&aliases)).await? {
+                updates.insert(update.alias.clone(), update);
+            }
+        }
+
+        // comments are not filtered by release, so comments on updates that are out of scope (and
+        // were therefore neither fetched nor stored) are dropped
+        let mut scoped = Vec::new();
+        for comment in comments {
+            let alias = match &comment.update {
+                Some(update) => Some(update.alias.clone()),
+                None => store.update_alias(comment.update_id)?,
+            };
+
+            let in_scope = match alias {
+                Some(alias) => updates.contains_key(&alias) || store.has_update(&alias)?,
+                None => false,
+            };
+
+            if in_scope {
+                scoped.push(comment);
+            }
+        }
+        comments = scoped;
+
+        println!("Fetching active overrides ...");
+        let mut query = OverrideQuery::new().expired(false);
+        if let Some(releases) = releases {
+            query = query.releases(releases);
+        }
+        overrides = recorder
+            .request(
+                &RequestKey::query("/overrides/", &OverridePageQuery::from_query(&query, 0))?,
+                bodhi.paginated_request(&query),
+            )
+            .await?;
+
+        // overrides that were active during the last run might have been expired since then
+        let active: HashSet<&str> = overrides.iter().map(|o| o.nvr.as_str()).collect();
+        let stale: Vec<String> = store
+            .active_overrides()?
+            .into_iter()
+            .filter(|nvr| !active.contains(nvr.as_str()))
+            .collect();
+
+        for nvr in stale {
+            match recorder
+                .optional(
+                    &RequestKey::get(format!("/overrides/{}", &nvr)),
+                    bodhi.request(&OverrideNVRQuery::new(&nvr)),
+                )
+                .await?
+            {
+                Some(over_ride) => overrides.push(over_ride),
+                None => removed_overrides.push(nvr),
+            }
+        }
+    } else {
+        println!("Fetching all updates (this will take a while) ...");
+
+        let mut query = UpdateQuery::new().rows_per_page(PAGE_ROWS);
+        if let Some(releases) = releases {
+            query = query.releases(releases);
+        }
+
+        let query = &query;
+        let result: Vec<Update> = checkpointed_query(
+            recorder,
+            &format!("{} sync", server_url),
+            "/updates/",
+            resume,
+            true,
+            |page| UpdatePageQuery::from_query(query, page),
+            |page_query| async move { bodhi.request(&page_query).await },
+        )
+        .await?;
+
+        for update in result {
+            updates.insert(update.alias.clone(), update);
+        }
+
+        println!("Fetching all overrides (this will take a while) ...");
+
+        let mut query = OverrideQuery::new().rows_per_page(PAGE_ROWS);
+        if let Some(releases) = releases {
+            query = query.releases(releases);
+        }
+
+        let query = &query;
+        overrides = checkpointed_query(
+            recorder,
+            &format!("{} sync", server_url),
+            "/overrides/",
+            resume,
+            true,
+            |page| OverridePageQuery::from_query(query, page),
+            |page_query| async move { bodhi.request(&page_query).await },
+        )
+        .await?;
+    }
+
+    let scoped_releases: Vec<&Release> = all_releases
+        .iter()
+        .filter(|release| releases.map_or(true, |releases| releases.contains(&release.name)))
+        .collect();
+    let release_names = override_releases(bodhi, recorder, store, &scoped_releases, &updates, &overrides).await?;
+
+    store.transaction(|store| {
+        // a full synchronization replaces everything, including data that is now out of scope
+        if !incremental {
+            store.clear_mirror()?;
+        }
+
+        for release in &all_releases {
+            store.insert_release(release)?;
+        }
+        store.replace_composes(&composes)?;
+        for update in updates.values() {
+            store.insert_update(update)?;
+        }
+        for comment in &comments {
+            store.insert_comment(comment, None)?;
+        }
+        for over_ride in &overrides {
+            store.insert_override(over_ride, release_names.get(&over_ride.nvr).map(|name| name.as_str()))?;
+        }
+        for nvr in &removed_overrides {
+            store.remove_override(nvr)?;
+        }
+
+        store.set_state("last_sync", &started.to_rfc3339())?;
+        store.set_state("scope", &scope)?;
+
+        Ok(())
+    })?;
+
+    let nested: usize = updates
+        .values()
+        .map(|update| update.comments.as_ref().map(|comments| comments.len()).unwrap_or(0))
+        .sum();
+
+    println!(
+        "Synchronized {} releases, {} updates, {} comments, and {} overrides.",
+        all_releases.len(),
+        updates.len(),
+        comments.len() + nested,
+        overrides.len()
+    );
+
+    Ok(())
+}
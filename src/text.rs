@@ -0,0 +1,109 @@
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const DEFAULT_EDITOR: &str = "vi";
+
+// everything below this line is removed from text that was composed in an editor; unlike lines
+// starting with '#', this does not interfere with markdown headings
+const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+/// This function reads text from a file, or from standard input if the path is `-`.
+pub fn read_text_file(path: &Path) -> Result<String, String> {
+    if path == Path::new("-") {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|error| format!("Failed to read from standard input: {}", error))?;
+        Ok(text)
+    } else {
+        std::fs::read_to_string(path).map_err(|error| format!("Failed to read {}: {}", path.display(), error))
+    }
+}
+
+/// This function opens `$EDITOR` (or `vi`, if it is not set) with the initial text and a
+/// commented template, and returns the edited text. Lines below the template marker are removed.
+pub fn edit_text(initial: &str, template: &str) -> Result<String, String> {
+    // the file is created with a random name and restrictive permissions, and is removed when
+    // it is dropped, whether editing succeeded or not
+    let mut file = tempfile::Builder::new()
+        .prefix("bodhi-cli-")
+        .suffix(".md")
+        .tempfile()
+        .map_err(|error| format!("Failed to create temporary file: {}", error))?;
+
+    let contents = format!(
+        "{}\n\n{}\n# Do not modify or remove the line above.\n# Everything below it will be ignored.\n#\n{}\n",
+        initial.trim_end(),
+        SCISSORS,
+        template
+            .lines()
+            .map(|line| format!("# {}", line).trim_end().to_owned())
+            .collect::<Vec<String>>()
+            .join("\n")
+    );
+
+    file.write_all(contents.as_bytes())
+        .and_then(|_| file.flush())
+        .map_err(|error| format!("Failed to write temporary file: {}", error))?;
+    let path = file.path();
+
+    let editor = std::env::var("EDITOR")
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| String::from(DEFAULT_EDITOR));
+
+    // $EDITOR can contain arguments (for example, "code --wait")
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or(DEFAULT_EDITOR);
+
+    let status = Command::new(program).args(parts).arg(path).status();
+
+    let result = match status {
+        Ok(status) if status.success() => {
+            std::fs::read_to_string(path).map_err(|error| format!("Failed to read temporary file: {}", error))
+        },
+        Ok(status) => Err(format!("Editor '{}' exited with {}.", editor, status)),
+        Err(error) => Err(format!("Failed to run editor '{}': {}", editor, error)),
+    };
+
+    drop(file);
+
+    let text = result?;
+    let text = match text.find(SCISSORS) {
+        Some(index) => &text[..index],
+        None => text.as_str(),
+    };
+
+    // the initial text is returned as-is if it was not changed, since whitespace was added around
+    // it for editing
+    if text.trim() == initial.trim() {
+        return Ok(initial.to_owned());
+    }
+
+    Ok(text.trim().to_owned())
+}
+
+/// This function returns text from the first of the given sources that is present: literal text
+/// from the command line, a file (or standard input), or an editor. It returns `None` if no source
+/// was specified, and an error if the resulting text is empty.
+pub fn text_input(
+    text: Option<String>,
+    file: Option<PathBuf>,
+    edit: bool,
+    initial: &str,
+    template: &str,
+) -> Result<Option<String>, String> {
+    let text = match (text, file, edit) {
+        (Some(text), _, _) => text,
+        (None, Some(file), _) => read_text_file(&file)?,
+        (None, None, true) => edit_text(initial, template)?,
+        (None, None, false) => return Ok(None),
+    };
+
+    if text.trim().is_empty() {
+        return Err(String::from("Aborting due to empty text."));
+    }
+
+    Ok(Some(text))
+}
@@ -0,0 +1,371 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use bodhi::*;
+
+use crate::cli::WatchTarget;
+use crate::notify::Notifier;
+use crate::query::query_update;
+use crate::record::{Recorder, RequestKey};
+
+/// settings for polling the server
+#[derive(Debug)]
+pub struct Polling {
+    /// initial interval between requests
+    pub interval: Duration,
+    /// maximum interval between requests, which is reached by doubling the interval
+    pub max_interval: Duration,
+    /// time after which polling stops
+    pub timeout: Option<Duration>,
+}
+
+/// reason for which watching an update stopped
+#[derive(Debug)]
+pub enum WatchOutcome {
+    Reached,
+    Obsoleted,
+    Unpushed,
+    TimedOut,
+}
+
+impl WatchOutcome {
+    /// This method returns the exit code for this outcome. Exit code 1 is reserved for errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            WatchOutcome::Reached => 0,
+            WatchOutcome::Obsoleted => 2,
+            WatchOutcome::Unpushed => 3,
+            WatchOutcome::TimedOut => 4,
+        }
+    }
+}
+
+/// subset of update state that is reported when it changes
+struct Snapshot {
+    status: String,
+    request: String,
+    karma: i32,
+    comments: HashSet<u32>,
+}
+
+impl Snapshot {
+    fn new(update: &Update) -> Self {
+        Snapshot {
+            status: update.status.to_string(),
+            request: update
+                .request
+                .map_or(String::from("none"), |request| request.to_string()),
+            karma: update.karma.unwrap_or_default(),
+            comments: update
+                .comments
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .map(|comment| comment.id)
+                .collect(),
+        }
+    }
+}
+
+fn reached(update: &Update, target: WatchTarget) -> bool {
+    match target {
+        WatchTarget::Pushed => {
+            update.pushed
+                && update.request.is_none()
+                && matches!(update.status, UpdateStatus::Testing | UpdateStatus::Stable)
+        },
+        WatchTarget::Stable => matches!(update.status, UpdateStatus::Stable),
+        WatchTarget::Testing => matches!(update.status, UpdateStatus::Testing | UpdateStatus::Stable),
+    }
+}
+
+fn timestamp() -> String {
+    chrono::Local::now().format("%H:%M:%S").to_string()
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+
+    if seconds >= 3600 {
+        format!("{}h {:02}m {:02}s", seconds / 3600, (seconds % 3600) / 60, seconds % 60)
+    } else {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    }
+}
+
+/// This function prints all differences between the previous and the current state of an update,
+/// and returns whether there were any. Notifications are collected for status changes (including
+/// pushes to stable) and for new comments with negative karma.
+fn print_differences(previous: &Snapshot, update: &Update, notifications: &mut Vec<(String, String)>) -> bool {
+    let current = Snapshot::new(update);
+    let mut changed = false;
+
+    if current.status != previous.status {
+        println!("[{}] status: {} -> {}", timestamp(), &previous.status, &current.status);
+        changed = true;
+
+        if matches!(update.status, UpdateStatus::Stable) {
+            notifications.push((
+                format!("Update {} was pushed to stable", &update.alias),
+                update.title.clone(),
+            ));
+        } else {
+            notifications.push((
+                format!("Update {} is now {}", &update.alias, &current.status),
+                format!("status: {} -> {}", &previous.status, &current.status),
+            ));
+        }
+    }
+
+    if current.request != previous.request {
+        println!(
+            "[{}] request: {} -> {}",
+            timestamp(),
+            &previous.request,
+            &current.request
+        );
+        changed = true;
+    }
+
+    if current.karma != previous.karma {
+        println!("[{}] karma: {} -> {}", timestamp(), previous.karma, current.karma);
+        changed = true;
+    }
+
+    for comment in update.comments.as_deref().unwrap_or_default() {
+        if !previous.comments.contains(&comment.id) {
+            println!(
+                "[{}] new comment by {} ({}): {}",
+                timestamp(),
+                &comment.user.name,
+                comment.karma,
+                comment.text.lines().next().unwrap_or_default()
+            );
+            changed = true;
+
+            if comment.karma == Karma::Negative {
+                notifications.push((
+                    format!("Negative karma for update {}", &update.alias),
+                    format!("{}: {}", &comment.user.name, &comment.text),
+                ));
+            }
+        }
+    }
+
+    changed
+}
+
+/// This function polls an update until it reaches the target state, is obsoleted or unpushed, or
+/// until the timeout is reached. The polling interval is doubled (up to the given maximum) every
+/// time nothing changed, and is reset to the initial interval when something changed.
+pub async fn watch_update(
+    bodhi: &BodhiClient,
+    recorder: &Recorder,
+    alias: &str,
+    target: WatchTarget,
+    polling: &Polling,
+    notifier: Option<&Notifier>,
+) -> Result<WatchOutcome, String> {
+    let start = Instant::now();
+
+    let mut update = query_update(bodhi, recorder, alias).await?;
+    let mut previous = Snapshot::new(&update);
+    let mut delay = polling.interval;
+
+    println!(
+        "[{}] Watching update {}: status {}, request {}, karma {}",
+        timestamp(),
+        alias,
+        &previous.status,
+        &previous.request,
+        previous.karma
+    );
+
+    loop {
+        if reached(&update, target) {
+            println!("[{}] Update {} reached {}.", timestamp(), alias, target);
+            return Ok(WatchOutcome::Reached);
+        }
+
+        match update.status {
+            UpdateStatus::Obsolete => {
+                println!("[{}] Update {} was obsoleted.", timestamp(), alias);
+                return Ok(WatchOutcome::Obsoleted);
+            },
+            UpdateStatus::Unpushed => {
+                println!("[{}] Update {} was unpushed.", timestamp(), alias);
+                return Ok(WatchOutcome::Unpushed);
+            },
+            _ => {},
+        }
+
+        if let Some(timeout) = polling.timeout {
+            let elapsed = start.elapsed();
+
+            if elapsed >= timeout {
+                println!(
+                    "[{}] Timed out waiting for update {} to reach {}.",
+                    timestamp(),
+                    alias,
+                    target
+                );
+                return Ok(WatchOutcome::TimedOut);
+            }
+
+            delay = delay.min(timeout - elapsed);
+        }
+
+        tokio::time::sleep(delay).await;
+
+        // failing to query the update once is not fatal, it is only retried later
+        match query_update(bodhi, recorder, alias).await {
+            Ok(current) => {
+                let mut notifications = Vec::new();
+                let changed = print_differences(&previous, &current, &mut notifications);
+                previous = Snapshot::new(&current);
+                update = current;
+
+                if let Some(notifier) = notifier {
+                    for (summary, body) in &notifications {
+                        notifier.send(summary, body).await;
+                    }
+                }
+
+                delay = if changed {
+                    polling.interval
+                } else {
+                    (delay * 2).min(polling.max_interval)
+                };
+            },
+            Err(error) => {
+                eprintln!("[{}] Failed to query update {}: {}", timestamp(), alias, error);
+                delay = (delay * 2).min(polling.max_interval);
+            },
+        }
+    }
+}
+
+/// This function polls a compose until it has finished, and prints its state transitions. Composes
+/// are removed from the server after they succeeded, so a compose that disappears has finished,
+/// too. Failed composes and timeouts are reported as errors.
+pub async fn wait_for_compose(
+    bodhi: &BodhiClient,
+    recorder: &Recorder,
+    release: &FedoraRelease,
+    request: ComposeRequest,
+    polling: &Polling,
+    notifier: Option<&Notifier>,
+) -> Result<(), String> {
+    let start = Instant::now();
+    let key = RequestKey::get(format!("/composes/{}/{}", release, request));
+
+    let mut previous: Option<ComposeState> = None;
+    let mut first = true;
+
+    loop {
+        let result: Result<Option<Compose>, String> = recorder
+            .optional(&key, bodhi.request(&ComposeReleaseRequestQuery::new(release, request)))
+            .await;
+
+        match result {
+            Ok(None) if first => {
+                println!("There is no compose for {} {}.", release, request);
+                return Ok(());
+            },
+            Ok(None) => {
+                let message = format!(
+                    "Compose for {} {} finished after {}.",
+                    release,
+                    request,
+                    format_elapsed(start.elapsed())
+                );
+                println!("[{}] {}", timestamp(), &message);
+
+                if let Some(notifier) = notifier {
+                    notifier.send(&message, "").await;
+                }
+
+                return Ok(());
+            },
+            Ok(Some(compose)) => {
+                if first {
+                    println!(
+                        "[{}] Compose for {} {} is {} (created {}).",
+                        timestamp(),
+                        release,
+                        request,
+                        compose.state,
+                        compose.date_created
+                    );
+                } else if let Some(state) = previous.filter(|state| *state != compose.state) {
+                    println!(
+                        "[{}] state: {} -> {} (after {})",
+                        timestamp(),
+                        state,
+                        compose.state,
+                        format_elapsed(start.elapsed())
+                    );
+                }
+
+                match compose.state {
+                    ComposeState::Success => {
+                        let message = format!(
+                            "Compose for {} {} succeeded after {}.",
+                            release,
+                            request,
+                            format_elapsed(start.elapsed())
+                        );
+                        println!("[{}] {}", timestamp(), &message);
+
+                        if let Some(notifier) = notifier {
+                            notifier.send(&message, "").await;
+                        }
+
+                        return Ok(());
+                    },
+                    ComposeState::Failed => {
+                        let message = compose
+                            .error_message
+                            .filter(|message| !message.is_empty())
+                            .unwrap_or_else(|| String::from("unknown error"));
+
+                        if let Some(notifier) = notifier {
+                            notifier
+                                .send(&format!("Compose for {} {} failed", release, request), &message)
+                                .await;
+                        }
+
+                        return Err(format!("Compose for {} {} failed: {}", release, request, message));
+                    },
+                    _ => {},
+                }
+
+                previous = Some(compose.state);
+            },
+            Err(error) if first => return Err(error),
+            // failing to query the compose once is not fatal, it is only retried later
+            Err(error) => eprintln!("[{}] Failed to query compose: {}", timestamp(), error),
+        }
+
+        first = false;
+
+        let mut delay = polling.interval;
+
+        if let Some(timeout) = polling.timeout {
+            let elapsed = start.elapsed();
+
+            if elapsed >= timeout {
+                return Err(format!(
+                    "Timed out waiting for compose for {} {} after {}.",
+                    release,
+                    request,
+                    format_elapsed(elapsed)
+                ));
+            }
+
+            delay = delay.min(timeout - elapsed);
+        }
+
+        tokio::time::sleep(delay).await;
+    }
+}
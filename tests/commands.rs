@@ -0,0 +1,1270 @@
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+use std::process::Stdio;
+
+mod common;
+use common::*;
+
+const TESTING: &str = "FEDORA-2024-0000000001";
+const STABLE: &str = "FEDORA-2024-0000000002";
+
+#[test]
+fn apply() {
+    let env = TestEnv::new();
+
+    let manifest = env.path().join("update.toml");
+    std::fs::write(
+        &manifest,
+        r#"
+builds = ["rust-new-0.1.0-1.fc40"]
+notes = "Initial packaging."
+bugs = [4242]
+type = "newpackage"
+stable_karma = 2
+requirements = ["fedora-ci.koji-build.tier0.functional"]
+"#,
+    )
+    .unwrap();
+    let path = manifest.to_str().unwrap();
+
+    let output = env.success(&["apply", path]);
+    assert!(output.contains("Creating new update:"));
+    assert!(output.contains("+ stable_karma: 2"));
+    assert!(output.contains("Update created."));
+
+    let alias = env.mock.state().updates.last().unwrap()["alias"]
+        .as_str()
+        .unwrap()
+        .to_owned();
+    let posts = || {
+        env.mock
+            .requests()
+            .iter()
+            .filter(|request| request.starts_with("POST /updates/"))
+            .count()
+    };
+    assert_eq!(posts(), 1);
+
+    let output = env.success(&["apply", path]);
+    assert!(output.contains(&format!("Update {} is up to date.", alias)));
+    assert_eq!(posts(), 1);
+
+    let contents = std::fs::read_to_string(&manifest).unwrap();
+    std::fs::write(&manifest, contents.replace("stable_karma = 2", "stable_karma = 3")).unwrap();
+
+    let output = env.success(&["apply", path]);
+    assert!(output.contains(&format!("Changes for update {}:", alias)));
+    assert!(output.contains("~ stable_karma: 2 -> 3"));
+    assert!(!output.contains("~ notes"));
+    assert_eq!(posts(), 2);
+
+    let state = env.mock.state();
+    let update = state.update(&alias).unwrap();
+    assert_eq!(update["stable_karma"], 3);
+    assert_eq!(update["bugs"][0]["bug_id"], 4242);
+    assert_eq!(state.updates.len(), 4);
+}
+
+#[test]
+fn build_info() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["build-info", "rust-foo-1.0.0-1.fc40"]);
+    assert!(output.contains("Signed:   yes"));
+    assert!(output.contains("Release:  F40"));
+    assert!(output.contains(&format!("Update:   {} (testing)", TESTING)));
+    assert!(output.contains("Override: active, expires 2030-01-01 00:00:00"));
+
+    let info = env.json(&["build-info", "rust-baz-0.3.0-1.fc39"]);
+    assert_eq!(info["release"], "F39");
+    assert_eq!(info["update"]["alias"], STABLE);
+    assert!(info["override"].is_null());
+
+    let error = env.failure(&["build-info", "rust-missing-1.0-1.fc40"]);
+    assert!(error.contains("Not found"));
+}
+
+#[test]
+fn changes() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["changes"]);
+    assert!(output.contains("Recorded the state of 1 updates and 1 overrides of tester."));
+
+    let output = env.success(&["changes"]);
+    assert!(output.contains("No changes since the last run."));
+
+    {
+        let mut state = env.mock.state();
+        let update = state.update_mut(TESTING).unwrap();
+        update["status"] = serde_json::json!("stable");
+        update["karma"] = serde_json::json!(0);
+        update["comments"].as_array_mut().unwrap().push(common::comment(
+            102,
+            1,
+            "bob",
+            -1,
+            "Breaks everything.",
+            "2024-05-03 11:00:00",
+        ));
+        state.over_ride_mut("rust-foo-1.0.0-1.fc40").unwrap()["expired_date"] =
+            serde_json::json!("2024-05-04 00:00:00");
+    }
+
+    let output = env.success(&["changes"]);
+    assert!(output.contains("status: testing -> stable"));
+    assert!(output.contains("karma: 1 -> 0"));
+    assert!(output.contains("new comment by bob (-1): Breaks everything."));
+    assert!(output.contains("Override for rust-foo-1.0.0-1.fc40 expired on 2024-05-04 00:00:00."));
+
+    let output = env.success(&["changes"]);
+    assert!(output.contains("No changes since the last run."));
+}
+
+#[test]
+fn comment() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["comment", "--update", TESTING, "--text", "LGTM", "--karma", "1"]);
+    assert!(output.contains("Comment created."));
+
+    let state = env.mock.state();
+    let update = state.update(TESTING).unwrap();
+    let comment = update["comments"].as_array().unwrap().last().unwrap();
+
+    assert_eq!(comment["text"], "LGTM");
+    assert_eq!(comment["user"]["name"], USERNAME);
+    assert_eq!(update["karma"], 2);
+}
+
+#[test]
+fn comment_text_file() {
+    let env = TestEnv::new();
+
+    let path = env.path().join("comment.md");
+    std::fs::write(&path, "# Results\n\nWorks on *x86_64*.\n").unwrap();
+    env.success(&["comment", "--update", TESTING, "--text-file", path.to_str().unwrap()]);
+
+    let mut child = env
+        .command(&["comment", "--update", TESTING, "--text-file", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"Read from stdin.").unwrap();
+    assert!(child.wait().unwrap().success());
+
+    let state = env.mock.state();
+    let texts: Vec<&str> = state.update(TESTING).unwrap()["comments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|comment| comment["text"].as_str().unwrap())
+        .collect();
+    assert_eq!(texts[2..], ["# Results\n\nWorks on *x86_64*.\n", "Read from stdin."]);
+}
+
+#[test]
+fn comment_editor_empty() {
+    let env = TestEnv::new();
+
+    let output = env
+        .command(&["comment", "--update", TESTING, "--edit"])
+        .env("EDITOR", "true")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Aborting due to empty text."));
+    assert_eq!(
+        env.mock.state().update(TESTING).unwrap()["comments"]
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn comment_feedback() {
+    let env = TestEnv::new();
+
+    env.success(&[
+        "comment",
+        "--update",
+        TESTING,
+        "--text",
+        "Fixes the crash, but not the test.",
+        "--bug-feedback",
+        "1001:+1",
+        "--testcase-feedback",
+        "QA:Testcase_foo:-1",
+    ]);
+
+    let state = env.mock.state();
+    let comment = state.update(TESTING).unwrap()["comments"]
+        .as_array()
+        .unwrap()
+        .last()
+        .unwrap()
+        .clone();
+    assert_eq!(comment["bug_feedback"][0]["bug_id"], 1001);
+    assert_eq!(comment["bug_feedback"][0]["karma"], 1);
+    assert_eq!(comment["testcase_feedback"][0]["testcase"]["name"], "QA:Testcase_foo");
+    assert_eq!(comment["testcase_feedback"][0]["karma"], -1);
+    drop(state);
+
+    let error = env.failure(&[
+        "comment",
+        "--update",
+        TESTING,
+        "--text",
+        "?",
+        "--bug-feedback",
+        "999:+1",
+    ]);
+    assert!(error.contains("Bug 999 is not associated with update"));
+
+    let error = env.failure(&[
+        "comment",
+        "--update",
+        TESTING,
+        "--text",
+        "?",
+        "--testcase-feedback",
+        "QA:Testcase_bar:-1",
+    ]);
+    assert!(error.contains("Test case QA:Testcase_bar is not associated with update"));
+
+    env.failure(&["comment", "--update", TESTING, "--text", "?", "--bug-feedback", "1001"]);
+}
+
+#[test]
+fn comment_info() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["comment-info", "101"]);
+    assert!(output.contains("Works for me."));
+
+    let comment = env.json(&["comment-info", "101"]);
+    assert_eq!(comment["user"]["name"], "alice");
+    assert_eq!(comment["update"]["alias"], TESTING);
+
+    env.failure(&["comment-info", "9999"]);
+}
+
+#[test]
+fn comment_wrong_password() {
+    let env = TestEnv::new().with_password("wrong");
+
+    env.failure(&["comment", "--update", TESTING, "--text", "LGTM"]);
+
+    let state = env.mock.state();
+    assert_eq!(state.update(TESTING).unwrap()["comments"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn compose_info() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["compose-info", "F40", "testing"]);
+    assert!(output.contains("Compose for F40 / testing"));
+    assert!(output.contains("requested"));
+
+    let error = env.failure(&["compose-info", "F39", "stable"]);
+    assert!(error.contains("Not found"));
+}
+
+#[test]
+fn compose_list() {
+    let env = TestEnv::new();
+
+    let composes = env.json(&["compose-list"]);
+    assert_eq!(composes.as_array().unwrap().len(), 1);
+    assert_eq!(composes[0]["state"], "requested");
+}
+
+#[test]
+fn compose_wait() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["compose-wait", "F39", "stable"]);
+    assert!(output.contains("There is no compose for F39 stable."));
+
+    let child = env
+        .command(&["compose-wait", "F40", "testing", "--interval", "1", "--timeout", "30"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // state changes are made under the lock, so every later request sees them
+    let path = "GET /composes/F40/testing";
+    env.mock.wait_for_requests(path, 1);
+    let seen = {
+        let mut state = env.mock.state();
+        state.composes[0]["state"] = serde_json::json!("punging");
+        state.request_count(path)
+    };
+    env.mock.wait_for_requests(path, seen + 1);
+    env.mock.state().composes.clear();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Compose for F40 testing is requested"));
+    assert!(stdout.contains("state: requested -> punging"));
+    assert!(stdout.contains("Compose for F40 testing finished after"));
+
+    env.mock
+        .state()
+        .composes
+        .push(compose("F40", "stable", "failed", &[STABLE]));
+    env.mock.state().composes[0]["error_message"] = serde_json::json!("Failed to sync repository.");
+    let error = env.failure(&["compose-wait", "F40", "stable"]);
+    assert!(error.contains("Compose for F40 stable failed: Failed to sync repository."));
+}
+
+#[test]
+fn create_override() {
+    let env = TestEnv::new();
+
+    let nvr = "rust-new-0.1.0-1.fc40";
+    let output = env.success(&["create-override", nvr, "--duration", "7", "--notes", "testing"]);
+    assert!(output.contains(&format!("Successfully created override for: {}", nvr)));
+
+    let state = env.mock.state();
+    let over_ride = state.over_ride(nvr).unwrap();
+    assert_eq!(over_ride["notes"], "testing");
+    assert_eq!(over_ride["submitter"]["name"], USERNAME);
+}
+
+#[test]
+fn create_update_override() {
+    let env = TestEnv::new();
+
+    let output = env.success(&[
+        "create-update-override",
+        STABLE,
+        "--duration",
+        "3",
+        "--notes",
+        "rebuilds",
+    ]);
+    assert!(output.contains("successfully created override for: rust-bar-2.0.0-1.fc39"));
+    assert!(output.contains("successfully created override for: rust-baz-0.3.0-1.fc39"));
+
+    let state = env.mock.state();
+    for nvr in ["rust-bar-2.0.0-1.fc39", "rust-baz-0.3.0-1.fc39"] {
+        let over_ride = state.over_ride(nvr).unwrap();
+        assert_eq!(over_ride["notes"], "rebuilds");
+        assert!(over_ride["expired_date"].is_null());
+    }
+}
+
+#[test]
+fn create_update_override_existing() {
+    let env = TestEnv::new();
+
+    let output = env.run(&["create-update-override", TESTING, "--duration", "3", "--notes", "again"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("failed to create override for rust-foo-1.0.0-1.fc40"));
+}
+
+#[test]
+fn create_update() {
+    let env = TestEnv::new();
+
+    let output = env.success(&[
+        "create-update",
+        "--builds",
+        "rust-new-0.1.0-1.fc40",
+        "--notes",
+        "Initial packaging.",
+        "--bugs",
+        "4242",
+        "--update-type",
+        "newpackage",
+        "--stable-karma",
+        "2",
+    ]);
+    assert!(output.contains("Update created."));
+
+    let state = env.mock.state();
+    let update = state.updates.last().unwrap();
+
+    assert_eq!(update["builds"][0]["nvr"], "rust-new-0.1.0-1.fc40");
+    assert_eq!(update["bugs"][0]["bug_id"], 4242);
+    assert_eq!(update["type"], "newpackage");
+    assert_eq!(update["stable_karma"], 2);
+    assert_eq!(update["notes"], "Initial packaging.");
+}
+
+#[test]
+fn dry_run() {
+    let env = TestEnv::new().with_password("wrong-password");
+
+    let output = env.success(&["--dry-run", "edit-update", TESTING, "--stable-karma", "5"]);
+    assert!(output.contains(&format!("POST {}/updates/", env.mock.url)));
+    assert!(output.contains("\"stable_karma\": 5"));
+    assert!(output.contains(&format!("\"edited\": \"{}\"", TESTING)));
+
+    let output = env.success(&["--dry-run", "expire-override", "rust-foo-1.0.0-1.fc40"]);
+    assert!(output.contains("\"expired\": true"));
+
+    let output = env.success(&[
+        "--dry-run",
+        "create-update-override",
+        STABLE,
+        "--duration",
+        "7",
+        "--notes",
+        "x",
+    ]);
+    assert_eq!(output.matches("POST ").count(), 2);
+
+    assert!(env.mock.requests().iter().all(|request| request.starts_with("GET ")));
+    assert_eq!(env.mock.state().update(TESTING).unwrap()["stable_karma"], 3);
+}
+
+#[test]
+fn dashboard() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["dashboard"]);
+    assert!(output.contains("Dashboard for tester"));
+    assert!(output.contains("Updates in testing (1):"));
+    assert!(output.contains("Eligible for stable (0):"));
+    assert!(output.contains(&format!(
+        "{} (rust-foo-1.0.0-1.fc40), karma 1, gating failed: failed gating",
+        TESTING
+    )));
+    assert!(output.contains("Overrides expiring within 7 days (0):"));
+
+    env.mock.state().update_mut(TESTING).unwrap()["karma"] = serde_json::json!(3);
+
+    // updates that are blocked by test gating are not eligible, even if their karma is high enough
+    let dashboard = env.json(&["dashboard"]);
+    assert!(dashboard["eligible"].as_array().unwrap().is_empty());
+
+    env.mock.state().update_mut(TESTING).unwrap()["test_gating_status"] = serde_json::json!("passed");
+
+    let dashboard = env.json(&["dashboard", "--expiring-days", "10000"]);
+    assert_eq!(dashboard["eligible"][0]["alias"], TESTING);
+    assert_eq!(dashboard["eligible"][0]["reason"], "karma 3 reached 3");
+    assert_eq!(dashboard["expiring_overrides"][0]["nvr"], "rust-foo-1.0.0-1.fc40");
+
+    // the server has the final say on whether the testing requirements are met
+    env.mock.state().update_mut(TESTING).unwrap()["meets_testing_requirements"] = serde_json::json!(false);
+
+    let dashboard = env.json(&["dashboard"]);
+    assert!(dashboard["eligible"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn easy_karma() {
+    let env = TestEnv::new();
+
+    let path = env.path().join("rpms.txt");
+    std::fs::write(
+        &path,
+        "rust-foo-1.0.0-1.fc40.src.rpm\npython-qux-3.1-2.fc40.src.rpm\n(none)\nglibc-2.39-1.fc40.src.rpm\n",
+    )
+    .unwrap();
+
+    // quitting right away does not print an empty summary
+    let mut child = env
+        .command(&["easy-karma", "--release", "F40", "--rpm-list", path.to_str().unwrap()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"q\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("No feedback was submitted."));
+    assert!(!stdout.contains("Summary:"));
+
+    let mut child = env
+        .command(&["easy-karma", "--release", "F40", "--rpm-list", path.to_str().unwrap()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"+1\n+1\n-1\nWorks well.\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("Update {}", TESTING)));
+    assert!(stdout.contains("Installed: rust-foo-1.0.0-1.fc40"));
+    assert!(stdout.contains("Comment created."));
+
+    let state = env.mock.state();
+    let comment = state.update(TESTING).unwrap()["comments"]
+        .as_array()
+        .unwrap()
+        .last()
+        .unwrap()
+        .clone();
+    assert_eq!(comment["user"]["name"], USERNAME);
+    assert_eq!(comment["karma"], 1);
+    assert_eq!(comment["text"], "Works well.");
+    assert_eq!(comment["bug_feedback"][0]["karma"], 1);
+    assert_eq!(comment["testcase_feedback"][0]["karma"], -1);
+    drop(state);
+
+    let output = env.success(&[
+        "easy-karma",
+        "--release",
+        "F40",
+        "--rpm-command",
+        "echo rust-foo-1.0.0-1.fc40.src.rpm",
+    ]);
+    assert!(output.contains(&format!("Skipping update {}", TESTING)));
+    assert!(output.contains("No updates in testing contain installed packages."));
+
+    // binary package names can not be matched against the source packages in updates
+    std::fs::write(&path, "python3-qux-3.1-2.fc40.noarch\n").unwrap();
+    let error = env.failure(&["easy-karma", "--release", "F40", "--rpm-list", path.to_str().unwrap()]);
+    assert!(error.contains("Not a source RPM: 'python3-qux-3.1-2.fc40.noarch'"));
+}
+
+#[test]
+fn edit_override() {
+    let env = TestEnv::new();
+
+    let nvr = "rust-foo-1.0.0-1.fc40";
+    let output = env.success(&["edit-override", nvr, "--duration", "14", "--notes", "extended", "--yes"]);
+    assert!(output.contains("~ notes: Override for rust-foo-1.0.0-1.fc40 -> extended"));
+    assert!(output.contains("Override edited."));
+
+    {
+        let state = env.mock.state();
+        let over_ride = state.over_ride(nvr).unwrap();
+        assert_eq!(over_ride["notes"], "extended");
+        assert!(over_ride["expired_date"].is_null());
+    }
+
+    // the expiration date is recomputed on every run, but only compared by day
+    let output = env.success(&["edit-override", nvr, "--duration", "14", "--notes", "extended", "--yes"]);
+    assert!(output.contains("Nothing to change for override"));
+
+    // without a terminal, changes are only applied with the '--yes' flag
+    let error = env.failure(&["edit-override", nvr, "--duration", "14", "--notes", "other"]);
+    assert!(error.contains("use the '--yes' flag"));
+    assert_eq!(env.mock.state().over_ride(nvr).unwrap()["notes"], "extended");
+}
+
+#[test]
+fn edit_update() {
+    let env = TestEnv::new();
+
+    let output = env.success(&[
+        "edit-update",
+        TESTING,
+        "--yes",
+        "--notes",
+        "Updated notes.",
+        "--add-bugs",
+        "31337",
+        "--severity",
+        "low",
+    ]);
+    assert!(output.contains("Update edited."));
+
+    let state = env.mock.state();
+    let update = state.update(TESTING).unwrap();
+    let bugs: Vec<u64> = update["bugs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|bug| bug["bug_id"].as_u64().unwrap())
+        .collect();
+
+    assert_eq!(update["notes"], "Updated notes.");
+    assert_eq!(update["severity"], "low");
+    assert_eq!(bugs, vec![1001, 31337]);
+}
+
+#[test]
+fn edit_update_confirm() {
+    let env = TestEnv::new();
+
+    // without a terminal, changes are only applied with the '--yes' flag
+    let mut child = env
+        .command(&["edit-update", TESTING, "--stable-karma", "5"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"y\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("~ stable_karma: 3 -> 5"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("use the '--yes' flag"));
+    assert_eq!(env.mock.state().update(TESTING).unwrap()["stable_karma"], 3);
+
+    let output = env.success(&["edit-update", TESTING, "--stable-karma", "5", "--yes"]);
+    assert!(output.contains("Update edited."));
+    assert_eq!(env.mock.state().update(TESTING).unwrap()["stable_karma"], 5);
+
+    let output = env.success(&["edit-update", TESTING, "--stable-karma", "5"]);
+    assert!(output.contains("Nothing to change"));
+
+    // standard input can not be used for both the notes and the confirmation prompt
+    let error = env.failure(&["edit-update", TESTING, "--notes-file", "-"]);
+    assert!(error.contains("requires the '--yes' flag"));
+}
+
+#[test]
+fn edit_update_editor() {
+    let env = TestEnv::new();
+
+    let notes = env.mock.state().update(TESTING).unwrap()["notes"]
+        .as_str()
+        .unwrap()
+        .to_owned();
+
+    let editor = env.path().join("editor.sh");
+    std::fs::write(
+        &editor,
+        "#!/bin/sh\ncp \"$1\" \"$HOME/editor-input\"\nsed -i '1s/^/Edited: /' \"$1\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&editor, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output = env
+        .command(&["edit-update", TESTING, "--edit", "--yes"])
+        .env("EDITOR", &editor)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let input = std::fs::read_to_string(env.path().join("editor-input")).unwrap();
+    assert!(input.starts_with(&notes));
+    assert!(input.contains(&format!("# Edit the notes of update {}.", TESTING)));
+
+    assert_eq!(
+        env.mock.state().update(TESTING).unwrap()["notes"],
+        format!("Edited: {}", notes)
+    );
+
+    // saving without changes does not change notes that end with a newline
+    env.mock.state().update_mut(TESTING).unwrap()["notes"] = serde_json::json!("Unchanged notes.\n");
+    let output = env
+        .command(&["edit-update", TESTING, "--edit", "--yes"])
+        .env("EDITOR", "true")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Nothing to change"));
+}
+
+#[test]
+fn expire_override() {
+    let env = TestEnv::new();
+
+    let nvr = "rust-foo-1.0.0-1.fc40";
+    let output = env.success(&["expire-override", nvr]);
+    assert!(output.contains("Override expired."));
+
+    assert!(!env.mock.state().over_ride(nvr).unwrap()["expired_date"].is_null());
+}
+
+#[test]
+fn expire_override_file() {
+    let env = TestEnv::new();
+
+    let path = env.path().join("overrides.txt");
+    std::fs::write(
+        &path,
+        "# overrides to expire\nrust-foo-1.0.0-1.fc40\nrust-qux-1.0-1.fc40\n",
+    )
+    .unwrap();
+
+    let output = env.run(&["expire-override", &format!("@{}", path.display())]);
+    assert!(!output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("  ok      rust-foo-1.0.0-1.fc40"));
+    assert!(stdout.contains("  failed  rust-qux-1.0-1.fc40"));
+    assert!(stdout.contains("1 of 2 items succeeded."));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("1 of 2 items failed."));
+
+    assert!(!env.mock.state().over_ride("rust-foo-1.0.0-1.fc40").unwrap()["expired_date"].is_null());
+}
+
+#[test]
+fn override_info() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["override-info", "rust-foo-1.0.0-1.fc40"]);
+    assert!(output.contains("Submitter: tester"));
+    assert!(output.contains("Expires:   2030-01-01 00:00:00"));
+    assert!(output.contains("remaining"));
+
+    let over_ride = env.json(&["override-info", "rust-bar-2.0.0-1.fc39"]);
+    assert_eq!(over_ride["nvr"], "rust-bar-2.0.0-1.fc39");
+    assert_eq!(over_ride["expired"], true);
+    assert!(over_ride["remaining_seconds"].is_null());
+
+    env.failure(&["override-info", "rust-qux-1.0-1.fc40"]);
+}
+
+#[test]
+fn push_eligible() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["push-eligible", "--yes"]);
+    assert!(output.contains("No updates are eligible for stable."));
+
+    {
+        let mut state = env.mock.state();
+        let update = state.update_mut(TESTING).unwrap();
+        update["test_gating_status"] = serde_json::json!("passed");
+        update["meets_testing_requirements"] = serde_json::json!(false);
+        update["karma"] = serde_json::json!(3);
+    }
+
+    // updates are never requested for stable if the server reports that they are not ready
+    let output = env.success(&["push-eligible", "--yes"]);
+    assert!(output.contains("No updates are eligible for stable."));
+
+    {
+        let mut state = env.mock.state();
+        let update = state.update_mut(TESTING).unwrap();
+        update["meets_testing_requirements"] = serde_json::json!(true);
+        update["karma"] = serde_json::json!(0);
+        update["stable_days"] = serde_json::json!(0);
+        update["date_testing"] = serde_json::json!("2024-01-01 00:00:00");
+    }
+
+    // a "stable_days" value of 0 does not make updates eligible right away
+    let output = env.success(&["push-eligible", "--yes"]);
+    assert!(output.contains("No updates are eligible for stable."));
+
+    env.mock.state().update_mut(TESTING).unwrap()["karma"] = serde_json::json!(3);
+
+    // without a terminal, stable requests are only submitted with the '--yes' flag
+    let output = env.run(&["push-eligible"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("{} (rust-foo-1.0.0-1.fc40): karma 3 reached 3", TESTING)));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("use the '--yes' flag"));
+    assert!(env.mock.state().update(TESTING).unwrap()["request"].is_null());
+
+    let output = env.success(&["push-eligible", "--yes"]);
+    assert!(output.contains(&format!("Update {} requested for stable.", TESTING)));
+    assert_eq!(env.mock.state().update(TESTING).unwrap()["request"], "stable");
+}
+
+#[test]
+fn query_builds() {
+    let env = TestEnv::new();
+
+    let builds = env.json(&["query-builds", "--updates", STABLE]);
+    let nvrs: Vec<&str> = builds
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|build| build["nvr"].as_str().unwrap())
+        .collect();
+    assert_eq!(nvrs, vec!["rust-bar-2.0.0-1.fc39", "rust-baz-0.3.0-1.fc39"]);
+
+    let builds = env.json(&["query-builds", "--packages", "python-qux", "--releases", "F40"]);
+    assert_eq!(builds.as_array().unwrap().len(), 1);
+    assert_eq!(builds[0]["nvr"], "python-qux-3.1-2.fc40");
+
+    let output = env.run(&["query-builds"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'--force'"));
+}
+
+#[test]
+fn query_comments() {
+    let env = TestEnv::new();
+
+    let comments = env.json(&["query-comments", "--updates", TESTING]);
+    let ids: Vec<u64> = comments
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|comment| comment["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![101, 100]);
+
+    let comments = env.json(&["query-comments", "--update-owners", "alice", "--ignore-users", "bodhi"]);
+    assert!(comments.as_array().unwrap().is_empty());
+
+    let comments = env.json(&["query-comments", "--since", "2024-05-02 00:00:00", "--users", "bodhi"]);
+    assert_eq!(comments.as_array().unwrap().len(), 2);
+
+    let output = env.run(&["query-comments", "--ignore-users", "bodhi"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'--force'"));
+}
+
+#[test]
+fn query_overrides() {
+    let env = TestEnv::new();
+
+    let overrides = env.json(&["query-overrides", "--expired", "false"]);
+    assert_eq!(overrides.as_array().unwrap().len(), 1);
+    assert_eq!(overrides[0]["nvr"], "rust-foo-1.0.0-1.fc40");
+
+    let overrides = env.json(&["query-overrides", "--users", "alice"]);
+    assert_eq!(overrides.as_array().unwrap().len(), 1);
+    assert_eq!(overrides[0]["nvr"], "rust-bar-2.0.0-1.fc39");
+}
+
+#[test]
+fn query_overrides_resume() {
+    let env = TestEnv::new();
+
+    {
+        let mut state = env.mock.state();
+        for number in 0..60 {
+            let nvr = format!("rust-many-{}-1.fc40", number);
+            state
+                .overrides
+                .push(over_ride(&nvr, "bob", "2024-06-10 12:00:00", None));
+        }
+        state.fail_page = Some(2);
+    }
+
+    let error = env.failure(&["query-overrides", "--users", "bob"]);
+    assert!(error.contains("Internal server error."));
+
+    // an override that is added before resuming moves the last result of page 1 to page 2
+    {
+        let mut state = env.mock.state();
+        let added = over_ride("rust-added-1-1.fc40", "bob", "2024-06-10 12:00:00", None);
+        state.overrides.insert(0, added);
+        state.fail_page = None;
+        state.requests.clear();
+    }
+
+    let overrides = env.json(&["query-overrides", "--users", "bob", "--resume"]);
+    let nvrs: Vec<&str> = overrides
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|over_ride| over_ride["nvr"].as_str().unwrap())
+        .collect();
+    assert_eq!(nvrs.len(), 60);
+    assert_eq!(nvrs.iter().filter(|nvr| **nvr == "rust-many-49-1.fc40").count(), 1);
+
+    let pages: Vec<String> = env
+        .mock
+        .requests()
+        .into_iter()
+        .filter(|request| request.starts_with("GET /overrides/"))
+        .collect();
+    assert_eq!(pages.len(), 1);
+    assert!(pages[0].contains("page=2"));
+}
+
+#[test]
+fn query_overrides_requires_force() {
+    let env = TestEnv::new();
+
+    let output = env.run(&["query-overrides"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'--force'"));
+    assert!(env
+        .mock
+        .requests()
+        .iter()
+        .all(|request| !request.contains("/overrides/")));
+
+    let overrides = env.json(&["query-overrides", "--force"]);
+    assert_eq!(overrides.as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn query_packages() {
+    let env = TestEnv::new();
+
+    let packages = env.json(&["query-packages", "--like", "rust-"]);
+    let names: Vec<&str> = packages
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|package| package["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["rust-bar", "rust-baz", "rust-foo"]);
+
+    let output = env.success(&["query-packages", "--name", "python-qux"]);
+    assert!(output.contains("python-qux (rpm)"));
+
+    let packages = env.json(&["query-packages", "--search", "QUX"]);
+    assert_eq!(packages.as_array().unwrap().len(), 1);
+
+    let output = env.run(&["query-packages"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'--force'"));
+}
+
+#[test]
+fn query_updates() {
+    let env = TestEnv::new();
+
+    let updates = env.json(&["query-updates", "--releases", "F40"]);
+    let aliases: Vec<&str> = updates
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|update| update["alias"].as_str().unwrap())
+        .collect();
+    assert_eq!(aliases, vec![TESTING, "FEDORA-2024-0000000003"]);
+
+    let updates = env.json(&["query-updates", "--packages", "rust-baz"]);
+    assert_eq!(updates.as_array().unwrap().len(), 1);
+    assert_eq!(updates[0]["alias"], STABLE);
+
+    let updates = env.json(&["query-updates", "--status", "testing", "--users", "alice"]);
+    assert!(updates.as_array().unwrap().is_empty());
+}
+
+#[test]
+fn query_users() {
+    let env = TestEnv::new();
+
+    let users = env.json(&["query-users", "--groups", "provenpackager"]);
+    assert_eq!(users.as_array().unwrap().len(), 1);
+    assert_eq!(users[0]["name"], "alice");
+
+    let users = env.json(&["query-users", "--like", "e"]);
+    let names: Vec<&str> = users
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|user| user["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["alice", "tester"]);
+
+    let output = env.run(&["query-users"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("'--force'"));
+}
+
+#[test]
+fn release_info() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["release-info", "F39"]);
+    assert!(output.contains("Release F39:"));
+    assert!(output.contains("f39-updates-testing"));
+
+    env.failure(&["release-info", "F41"]);
+}
+
+#[test]
+fn release_list() {
+    let env = TestEnv::new();
+
+    let releases = env.json(&["release-list"]);
+    let names: Vec<&str> = releases
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|release| release["name"].as_str().unwrap())
+        .collect();
+
+    assert_eq!(names, vec!["F39", "F40"]);
+}
+
+#[test]
+fn sql() {
+    let env = TestEnv::new();
+
+    let error = env.failure(&["sql", "SELECT alias FROM updates"]);
+    assert!(error.contains("bodhi-cli sync"));
+
+    env.success(&["sync"]);
+
+    let rows = env.json(&["sql", "SELECT alias, karma FROM updates WHERE status = 'testing'"]);
+    assert_eq!(rows, serde_json::json!([{ "alias": TESTING, "karma": 1 }]));
+
+    let rows = env.json(&["sql", "SELECT nvr FROM active_overrides"]);
+    assert_eq!(rows, serde_json::json!([{ "nvr": "rust-foo-1.0.0-1.fc40" }]));
+
+    let error = env.failure(&["sql", "DELETE FROM updates"]);
+    assert!(error.contains("read-only"));
+}
+
+#[test]
+fn sync() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["sync"]);
+    assert!(output.contains("Synchronized 2 releases, 3 updates, 4 comments, and 2 overrides."));
+
+    // later runs only fetch data that changed since the last run
+    env.mock.state().requests.clear();
+    let output = env.success(&["-v", "sync"]);
+    assert!(output.contains("Fetching updates changed since"));
+    assert!(output.contains("updates    3"));
+    assert!(env
+        .mock
+        .requests()
+        .iter()
+        .any(|request| request.starts_with("GET /updates/") && request.contains("modified_since=")));
+
+    // changing the scope fetches everything again, and removes data that is now out of scope
+    let output = env.success(&["sync", "--releases", "F39"]);
+    assert!(output.contains("Synchronized 2 releases, 1 updates"));
+    let updates = env.json(&["--offline", "query-updates"]);
+    assert_eq!(updates.as_array().unwrap().len(), 1);
+    assert_eq!(updates[0]["alias"], STABLE);
+    let overrides = env.json(&["--offline", "query-overrides"]);
+    assert_eq!(overrides.as_array().unwrap().len(), 1);
+
+    // new comments on updates that are out of scope are not stored by incremental runs
+    env.mock.state().update_mut(TESTING).unwrap()["comments"]
+        .as_array_mut()
+        .unwrap()
+        .push(common::comment(103, 1, "bob", 0, "Any news?", "2099-01-01 00:00:00"));
+    let output = env.success(&["sync", "--releases", "F39"]);
+    assert!(output.contains("Fetching comments posted since"));
+    let sql = format!("SELECT id FROM comments WHERE update_alias IS NOT '{}'", STABLE);
+    let rows = env.json(&["sql", &sql]);
+    assert!(rows.as_array().unwrap().is_empty());
+}
+
+#[test]
+fn offline() {
+    let env = TestEnv::new();
+
+    // the release of an override is known even if the release has no updates
+    {
+        let mut state = env.mock.state();
+        state.releases.push(common::release("F41", "pending"));
+        state.overrides.push(common::over_ride(
+            "rust-foo-1.1.0-1.fc41",
+            USERNAME,
+            "2030-01-01 00:00:00",
+            None,
+        ));
+    }
+
+    env.success(&["sync"]);
+    env.mock.state().requests.clear();
+
+    let overrides = env.json(&["--offline", "query-overrides", "--releases", "F41"]);
+    assert_eq!(overrides.as_array().unwrap().len(), 1);
+    assert_eq!(overrides[0]["nvr"], "rust-foo-1.1.0-1.fc41");
+
+    let updates = env.json(&["--offline", "query-updates", "--releases", "F39"]);
+    assert_eq!(updates.as_array().unwrap().len(), 1);
+    assert_eq!(updates[0]["alias"], STABLE);
+
+    let overrides = env.json(&["--offline", "query-overrides", "--releases", "F40"]);
+    assert_eq!(overrides.as_array().unwrap().len(), 1);
+
+    let output = env.success(&["--offline", "release-info", "F40"]);
+    assert!(output.contains("Release F40:"));
+
+    let error = env.failure(&["--offline", "comment", "--update", TESTING, "--text", "LGTM"]);
+    assert!(error.contains("can not be used with '--offline'"));
+
+    assert!(env.mock.requests().is_empty());
+}
+
+#[test]
+fn record_replay() {
+    let env = TestEnv::new();
+    let recording = env.path().join("recording");
+    let recording = recording.to_str().unwrap();
+
+    let recorded = env.success(&["--record", recording, "query-updates", "--releases", "F40"]);
+    env.mock.state().requests.clear();
+
+    let replayed = env.success(&["--replay", recording, "query-updates", "--releases", "F40"]);
+    assert_eq!(recorded, replayed);
+    assert!(env.mock.requests().is_empty());
+
+    let error = env.failure(&["--replay", recording, "release-list"]);
+    assert!(error.contains("does not match"));
+
+    // requests with different query parameters must not be answered from the recording
+    let error = env.failure(&["--replay", recording, "query-updates", "--releases", "F39"]);
+    assert!(error.contains("does not match"));
+    assert!(error.contains("F39"));
+}
+
+#[test]
+fn record_replay_post() {
+    let env = TestEnv::new();
+    let recording = env.path().join("recording");
+    let recording = recording.to_str().unwrap();
+
+    let nvr = "rust-new-0.1.0-1.fc40";
+    let args = ["create-override", nvr, "--duration", "7", "--notes", "testing"];
+
+    let mut record_args = vec!["--record", recording];
+    record_args.extend(args);
+    let recorded = env.success(&record_args);
+    env.mock.state().requests.clear();
+
+    // expiration dates are computed from the current time, but only the day is part of the key
+    let mut replay_args = vec!["--replay", recording];
+    replay_args.extend(args);
+    let replayed = env.success(&replay_args);
+    assert_eq!(recorded, replayed);
+    assert!(env.mock.requests().is_empty());
+
+    let error = env.failure(&[
+        "--replay",
+        recording,
+        "create-override",
+        nvr,
+        "--duration",
+        "7",
+        "--notes",
+        "other",
+    ]);
+    assert!(error.contains("does not match"));
+
+    // recording into a directory with a previous recording would mix requests from both runs
+    let error = env.failure(&record_args);
+    assert!(error.contains("not empty"));
+}
+
+#[test]
+fn update_info() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["update-info", TESTING]);
+    assert!(output.contains("Karma:        1 (+1 / -0)"));
+    assert!(output.contains("1001: Bug 1001 [+1 (alice)]"));
+    assert!(output.contains("QA:Testcase_foo [+1 (alice)]"));
+    assert!(output.contains("Gating:       failed"));
+    assert!(output.contains("    Works for me."));
+
+    let update = env.json(&["update-info", TESTING]);
+    assert_eq!(update["alias"], TESTING);
+    assert_eq!(update["comments"].as_array().unwrap().len(), 2);
+
+    env.failure(&["update-info", "FEDORA-2024-0000000099"]);
+}
+
+#[test]
+fn update_request() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["update-request", TESTING, "stable"]);
+    assert!(output.contains("Update requested for stable."));
+
+    assert_eq!(env.mock.state().update(TESTING).unwrap()["request"], "stable");
+}
+
+#[test]
+fn update_request_stdin() {
+    let env = TestEnv::new();
+
+    let aliases = env.success(&["query-updates", "--releases", "F40", "--fields", "alias"]);
+    assert_eq!(aliases, format!("{}\nFEDORA-2024-0000000003\n", TESTING));
+
+    let fields = env.success(&[
+        "query-updates",
+        "--alias",
+        TESTING,
+        "--fields",
+        "alias,release.name,karma",
+    ]);
+    assert_eq!(fields, format!("{}\tF40\t1\n", TESTING));
+
+    let mut child = env
+        .command(&["update-request", "-", "stable"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(aliases.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("2 of 2 items succeeded."));
+
+    let state = env.mock.state();
+    assert_eq!(state.update(TESTING).unwrap()["request"], "stable");
+    assert_eq!(state.update("FEDORA-2024-0000000003").unwrap()["request"], "stable");
+}
+
+#[test]
+fn user_info() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["user-info", "alice"]);
+    assert!(output.contains("Groups: packager, provenpackager"));
+
+    let user = env.json(&["user-info", USERNAME]);
+    assert_eq!(user["name"], USERNAME);
+
+    env.failure(&["user-info", "nobody"]);
+}
+
+#[test]
+fn waive_tests() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["waive-tests", TESTING, "flaky test"]);
+    assert!(output.contains("Tests waived."));
+
+    assert_eq!(
+        env.mock.state().update(TESTING).unwrap()["test_gating_status"],
+        "passed"
+    );
+}
+
+#[test]
+fn watch() {
+    let env = TestEnv::new();
+
+    let output = env.success(&["watch", TESTING, "--until", "testing"]);
+    assert!(output.contains(&format!("Update {} reached testing.", TESTING)));
+
+    // without a session bus, notifications are disabled, but watching still works
+    let output = env.success(&["watch", TESTING, "--until", "testing", "--notify"]);
+    assert!(output.contains("notifications are disabled"));
+    assert!(output.contains(&format!("Update {} reached testing.", TESTING)));
+
+    let pending = "FEDORA-2024-0000000003";
+    let output = env.run(&[
+        "watch",
+        pending,
+        "--until",
+        "stable",
+        "--interval",
+        "1",
+        "--timeout",
+        "1",
+    ]);
+    assert_eq!(output.status.code(), Some(4));
+
+    env.mock.state().requests.clear();
+    let child = env
+        .command(&[
+            "watch",
+            pending,
+            "--until",
+            "stable",
+            "--interval",
+            "1",
+            "--timeout",
+            "30",
+        ])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    env.mock.wait_for_requests(&format!("GET /updates/{}", pending), 1);
+    env.mock.state().update_mut(pending).unwrap()["status"] = serde_json::json!("stable");
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("status: pending -> stable"));
+
+    env.mock.state().update_mut(pending).unwrap()["status"] = serde_json::json!("obsolete");
+    let output = env.run(&["watch", pending, "--until", "stable"]);
+    assert_eq!(output.status.code(), Some(2));
+}
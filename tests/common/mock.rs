@@ -0,0 +1,1157 @@
+//! minimal in-memory implementation of the bodhi REST API (and of the OpenID login flow)
+//!
+//! The server handles every connection on a separate thread and closes it after sending a single
+//! response. All state is kept in memory, and is shared with the test that started the server, so
+//! tests can both prepare data and check the effects of commands that modify data.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+use serde_json::{json, Value};
+
+pub const USERNAME: &str = "tester";
+pub const PASSWORD: &str = "correct-horse";
+
+const CSRF_TOKEN: &str = "mock-csrf-token";
+const SESSION_COOKIE: &str = "bodhi-session=mock";
+const NOW: &str = "2024-06-01 12:00:00";
+
+/// in-memory data of the mock server
+#[derive(Debug, Default)]
+pub struct State {
+    pub releases: Vec<Value>,
+    pub updates: Vec<Value>,
+    pub overrides: Vec<Value>,
+    pub composes: Vec<Value>,
+    /// method and path (including query string) of all requests that were received
+    pub requests: Vec<String>,
+    /// page number for which all paginated requests fail (for simulating interrupted queries)
+    pub fail_page: Option<usize>,
+    next_id: u64,
+}
+
+impl State {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    pub fn update(&self, alias: &str) -> Option<&Value> {
+        self.updates.iter().find(|update| update["alias"] == alias)
+    }
+
+    pub fn update_mut(&mut self, alias: &str) -> Option<&mut Value> {
+        self.updates.iter_mut().find(|update| update["alias"] == alias)
+    }
+
+    /// This method returns the number of received requests that start with the given method and
+    /// path.
+    pub fn request_count(&self, prefix: &str) -> usize {
+        self.requests
+            .iter()
+            .filter(|request| request.starts_with(prefix))
+            .count()
+    }
+
+    pub fn over_ride(&self, nvr: &str) -> Option<&Value> {
+        self.overrides.iter().find(|over_ride| over_ride["nvr"] == nvr)
+    }
+
+    pub fn over_ride_mut(&mut self, nvr: &str) -> Option<&mut Value> {
+        self.overrides.iter_mut().find(|over_ride| over_ride["nvr"] == nvr)
+    }
+
+    /// all builds that are part of an update or have a buildroot override, and the alias of the
+    /// update they belong to (if any)
+    fn builds(&self) -> Vec<(Value, Option<String>)> {
+        let mut builds: Vec<(Value, Option<String>)> = Vec::new();
+
+        for update in &self.updates {
+            for build in update["builds"].as_array().into_iter().flatten() {
+                builds.push((build.clone(), Some(text(&update["alias"]))));
+            }
+        }
+
+        for over_ride in &self.overrides {
+            if !builds.iter().any(|(build, _)| build["nvr"] == over_ride["nvr"]) {
+                builds.push((over_ride["build"].clone(), None));
+            }
+        }
+
+        builds
+    }
+
+    /// all packages with builds in updates or overrides, sorted by name
+    fn packages(&self) -> Vec<Value> {
+        let mut names: Vec<String> = self
+            .builds()
+            .iter()
+            .map(|(build, _)| package_name(&text(&build["nvr"])).to_owned())
+            .collect();
+        names.sort();
+        names.dedup();
+
+        names.iter().map(|name| package(name)).collect()
+    }
+
+    /// all users who submitted updates, comments, or overrides, sorted by name
+    fn users(&self) -> Vec<Value> {
+        let mut names: Vec<String> = Vec::new();
+
+        for update in &self.updates {
+            names.push(text(&update["user"]["name"]));
+        }
+        for comment in self.comments() {
+            names.push(text(&comment["user"]["name"]));
+        }
+        for over_ride in &self.overrides {
+            names.push(text(&over_ride["submitter"]["name"]));
+        }
+
+        names.sort();
+        names.dedup();
+
+        names.iter().map(|name| user(name)).collect()
+    }
+
+    /// all comments, including a copy of the update they belong to (as returned by `/comments/`)
+    fn comments(&self) -> Vec<Value> {
+        let mut comments = Vec::new();
+
+        for update in &self.updates {
+            let mut summary = update.clone();
+            summary["comments"] = Value::Null;
+
+            for comment in update["comments"].as_array().into_iter().flatten() {
+                let mut comment = comment.clone();
+                comment["update"] = summary.clone();
+                comments.push(comment);
+            }
+        }
+
+        comments.sort_by(|a, b| b["timestamp"].as_str().cmp(&a["timestamp"].as_str()));
+        comments
+    }
+}
+
+pub fn user(name: &str) -> Value {
+    let groups = match name {
+        "alice" => json!([{ "name": "packager" }, { "name": "provenpackager" }]),
+        _ => json!([{ "name": "packager" }]),
+    };
+
+    json!({
+        "avatar": null,
+        "email": null,
+        "groups": groups,
+        "id": name.len(),
+        "name": name,
+        "openid": null,
+    })
+}
+
+pub fn release(name: &str, state: &str) -> Value {
+    let version = name.trim_start_matches('F');
+
+    json!({
+        "branch": format!("f{}", version),
+        "candidate_tag": format!("f{}-updates-candidate", version),
+        "composed_by_bodhi": true,
+        "create_automatic_updates": false,
+        "dist_tag": format!("f{}", version),
+        "eol": null,
+        "id_prefix": "FEDORA",
+        "long_name": format!("Fedora {}", version),
+        "mail_template": "fedora_errata_template",
+        "name": name,
+        "override_tag": format!("f{}-override", version),
+        "package_manager": "dnf",
+        "pending_signing_tag": format!("f{}-signing-pending", version),
+        "pending_stable_tag": format!("f{}-updates-pending", version),
+        "pending_testing_tag": format!("f{}-updates-testing-pending", version),
+        "stable_tag": format!("f{}-updates", version),
+        "state": state,
+        "testing_repository": "updates-testing",
+        "testing_tag": format!("f{}-updates-testing", version),
+        "version": version,
+    })
+}
+
+fn release_id(name: &str) -> u32 {
+    match name {
+        "F39" => 1,
+        "F41" => 3,
+        _ => 2,
+    }
+}
+
+/// This function determines the release of a build from the dist tag in its NVR.
+fn release_of(nvr: &str) -> &'static str {
+    if nvr.ends_with(".fc39") {
+        "F39"
+    } else if nvr.ends_with(".fc41") {
+        "F41"
+    } else {
+        "F40"
+    }
+}
+
+pub fn build(nvr: &str) -> Value {
+    json!({
+        "epoch": 0,
+        "nvr": nvr,
+        "release_id": release_id(release_of(nvr)),
+        "signed": true,
+        "type": "rpm",
+    })
+}
+
+pub fn package(name: &str) -> Value {
+    json!({
+        "name": name,
+        "requirements": null,
+        "type": "rpm",
+    })
+}
+
+pub fn bug(bug_id: u64) -> Value {
+    json!({
+        "bug_id": bug_id,
+        "parent": false,
+        "security": false,
+        "title": format!("Bug {}", bug_id),
+    })
+}
+
+pub fn bug_feedback(bug_id: u64, comment_id: u64, karma: i64) -> Value {
+    json!({
+        "bug": bug(bug_id),
+        "bug_id": bug_id,
+        "comment_id": comment_id,
+        "karma": karma,
+    })
+}
+
+pub fn test_case(name: &str) -> Value {
+    json!({
+        "name": name,
+        "package": null,
+    })
+}
+
+pub fn testcase_feedback(name: &str, comment_id: u64, karma: i64) -> Value {
+    json!({
+        "comment_id": comment_id,
+        "karma": karma,
+        "testcase": test_case(name),
+        "testcase_id": name.len(),
+    })
+}
+
+pub fn comment(id: u64, update_id: u64, author: &str, karma: i64, text: &str, timestamp: &str) -> Value {
+    json!({
+        "bug_feedback": [],
+        "id": id,
+        "karma": karma,
+        "karma_critpath": 0,
+        "testcase_feedback": [],
+        "text": text,
+        "timestamp": timestamp,
+        "update": null,
+        "update_id": update_id,
+        "user": user(author),
+        "user_id": author.len(),
+    })
+}
+
+pub fn update(alias: &str, id: u64, nvrs: &[&str], status: &str, submitter: &str, submitted: &str) -> Value {
+    let release_name = release_of(nvrs.first().copied().unwrap_or_default());
+
+    let mut update = json!({
+        "alias": alias,
+        "autokarma": true,
+        "autotime": true,
+        "bugs": [bug(1000 + id)],
+        "builds": nvrs.iter().map(|nvr| build(nvr)).collect::<Vec<Value>>(),
+        "close_bugs": true,
+        "comments": [comment(id * 100, id, "bodhi", 0, "This update has been submitted for testing.", submitted)],
+        "compose": null,
+        "content_type": "rpm",
+        "critpath": false,
+        "critpath_groups": null,
+        "date_approved": null,
+        "date_modified": null,
+        "date_pushed": null,
+        "date_stable": null,
+        "date_submitted": submitted,
+        "date_testing": null,
+    });
+
+    // the update object is split in two, because it is too large for a single json! invocation
+    let rest = json!({
+        "display_name": "",
+        "from_tag": null,
+        "karma": 0,
+        "locked": false,
+        "meets_testing_requirements": true,
+        "notes": format!("Update for {}", nvrs.join(", ")),
+        "pushed": false,
+        "release": release(release_name, "current"),
+        "request": null,
+        "require_bugs": false,
+        "require_testcases": false,
+        "requirements": "",
+        "severity": "unspecified",
+        "stable_days": 7,
+        "stable_karma": 3,
+        "status": status,
+        "suggest": "unspecified",
+        "test_cases": [],
+        "test_gating_status": "failed",
+        "title": nvrs.join(" "),
+        "type": "bugfix",
+        "unstable_karma": -3,
+        "updateid": null,
+        "url": format!("https://bodhi.example.com/updates/{}", alias),
+        "user": user(submitter),
+        "version_hash": "0123456789abcdef",
+    });
+
+    if let (Some(update), Value::Object(rest)) = (update.as_object_mut(), rest) {
+        update.extend(rest);
+    }
+
+    update
+}
+
+pub fn over_ride(nvr: &str, submitter: &str, expiration: &str, expired: Option<&str>) -> Value {
+    json!({
+        "build": build(nvr),
+        "build_id": 1,
+        "expiration_date": expiration,
+        "expired_date": expired,
+        "notes": format!("Override for {}", nvr),
+        "nvr": nvr,
+        "submission_date": "2024-05-01 10:00:00",
+        "submitter": user(submitter),
+        "submitter_id": submitter.len(),
+    })
+}
+
+pub fn compose(release_name: &str, request: &str, state: &str, aliases: &[&str]) -> Value {
+    json!({
+        "checkpoints": "{}",
+        "content_type": "rpm",
+        "date_created": "2024-05-30 08:00:00",
+        "error_message": null,
+        "release": release(release_name, "current"),
+        "release_id": release_id(release_name),
+        "request": request,
+        "security": false,
+        "state": state,
+        "state_date": "2024-05-30 08:30:00",
+        "update_summary": aliases
+            .iter()
+            .map(|alias| json!({ "alias": alias, "title": alias }))
+            .collect::<Vec<Value>>(),
+    })
+}
+
+/// This function returns the data that a new mock server starts with.
+fn fixtures() -> State {
+    let mut updates = vec![
+        update(
+            "FEDORA-2024-0000000001",
+            1,
+            &["rust-foo-1.0.0-1.fc40"],
+            "testing",
+            USERNAME,
+            "2024-05-01 10:00:00",
+        ),
+        update(
+            "FEDORA-2024-0000000002",
+            2,
+            &["rust-bar-2.0.0-1.fc39", "rust-baz-0.3.0-1.fc39"],
+            "stable",
+            "alice",
+            "2024-05-02 10:00:00",
+        ),
+        update(
+            "FEDORA-2024-0000000003",
+            3,
+            &["python-qux-3.1-2.fc40"],
+            "pending",
+            "alice",
+            "2024-05-03 10:00:00",
+        ),
+    ];
+
+    updates[0]["comments"]
+        .as_array_mut()
+        .expect("fixture comments must be an array")
+        .push(comment(101, 1, "alice", 1, "Works for me.", "2024-05-02 11:00:00"));
+    updates[0]["comments"][1]["bug_feedback"] = json!([bug_feedback(1001, 101, 1)]);
+    updates[0]["comments"][1]["testcase_feedback"] = json!([testcase_feedback("QA:Testcase_foo", 101, 1)]);
+    updates[0]["test_cases"] = json!([test_case("QA:Testcase_foo")]);
+    updates[0]["karma"] = json!(1);
+    updates[1]["date_pushed"] = json!("2024-05-03 10:00:00");
+    updates[1]["date_stable"] = json!("2024-05-10 10:00:00");
+    updates[1]["pushed"] = json!(true);
+
+    State {
+        releases: vec![release("F39", "current"), release("F40", "current")],
+        updates,
+        overrides: vec![
+            over_ride("rust-foo-1.0.0-1.fc40", USERNAME, "2030-01-01 00:00:00", None),
+            over_ride(
+                "rust-bar-2.0.0-1.fc39",
+                "alice",
+                "2024-05-08 00:00:00",
+                Some("2024-05-08 00:00:00"),
+            ),
+        ],
+        composes: vec![compose("F40", "testing", "requested", &["FEDORA-2024-0000000001"])],
+        requests: Vec::new(),
+        fail_page: None,
+        next_id: 1000,
+    }
+}
+
+#[derive(Debug)]
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, Vec<String>>,
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    body: String,
+    cookie: Option<&'static str>,
+}
+
+impl Response {
+    fn json(status: u16, value: Value) -> Self {
+        Response {
+            status,
+            body: value.to_string(),
+            cookie: None,
+        }
+    }
+
+    fn not_found() -> Self {
+        Response::json(404, json!({ "errors": [], "status": "error" }))
+    }
+
+    fn error(status: u16, name: &str, description: &str) -> Self {
+        Response::json(
+            status,
+            json!({
+                "errors": [{ "location": "body", "name": name, "description": description }],
+                "status": "error",
+            }),
+        )
+    }
+}
+
+fn decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'+' => decoded.push(b' '),
+            b'%' if pos + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[pos + 1..pos + 3]).unwrap_or_default();
+
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        pos += 2;
+                    },
+                    Err(_) => decoded.push(b'%'),
+                }
+            },
+            byte => decoded.push(byte),
+        }
+        pos += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn parse_params(input: &str) -> HashMap<String, Vec<String>> {
+    let mut params: HashMap<String, Vec<String>> = HashMap::new();
+
+    for pair in input.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        params.entry(decode(key)).or_default().push(decode(value));
+    }
+
+    params
+}
+
+fn read_request(stream: &mut TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream);
+
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_owned();
+    let target = parts.next()?.to_owned();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).ok()?;
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_owned());
+        }
+    }
+
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body).ok()?;
+
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+
+    Some(Request {
+        method,
+        path: decode(path),
+        query: parse_params(query),
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+fn write_response(stream: &mut TcpStream, response: Response) {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        _ => "Error",
+    };
+
+    let cookie = match response.cookie {
+        Some(cookie) => format!("Set-Cookie: {}; Path=/\r\n", cookie),
+        None => String::new(),
+    };
+
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n",
+        response.status,
+        reason,
+        response.body.len(),
+        cookie
+    );
+
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(response.body.as_bytes());
+    let _ = stream.flush();
+}
+
+/// This function renders a value as a string in the same way as it is encoded in query strings.
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.to_owned(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn package_name(nvr: &str) -> &str {
+    let mut parts = nvr.rsplitn(3, '-');
+    parts.next();
+    parts.next();
+    parts.next().unwrap_or(nvr)
+}
+
+fn matches_update(update: &Value, params: &HashMap<String, Vec<String>>) -> bool {
+    let nvrs = || {
+        update["builds"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|b| text(&b["nvr"]))
+    };
+    let date = |field: &str| update[field].as_str().map(String::from);
+
+    params.iter().all(|(key, values)| match key.as_str() {
+        "alias" => values.contains(&text(&update["alias"])),
+        "bugs" => update["bugs"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|bug| values.contains(&text(&bug["bug_id"]))),
+        "builds" => nvrs().any(|nvr| values.contains(&nvr)),
+        "packages" => nvrs().any(|nvr| values.iter().any(|value| value == package_name(&nvr))),
+        "releases" => values.contains(&text(&update["release"]["name"])),
+        "user" => values.contains(&text(&update["user"]["name"])),
+        "content_type" | "critpath" | "locked" | "pushed" | "request" | "severity" | "status" | "suggest" | "type" => {
+            values.contains(&text(&update[key.as_str()]))
+        },
+        "modified_since" => date("date_modified").is_some_and(|date| date >= values[0]),
+        "modified_before" => date("date_modified").is_some_and(|date| date < values[0]),
+        "pushed_since" => date("date_pushed").is_some_and(|date| date >= values[0]),
+        "pushed_before" => date("date_pushed").is_some_and(|date| date < values[0]),
+        "submitted_since" => date("date_submitted").is_some_and(|date| date >= values[0]),
+        "submitted_before" => date("date_submitted").is_some_and(|date| date < values[0]),
+        _ => true,
+    })
+}
+
+fn matches_override(over_ride: &Value, params: &HashMap<String, Vec<String>>) -> bool {
+    params.iter().all(|(key, values)| match key.as_str() {
+        "builds" => values.contains(&text(&over_ride["nvr"])),
+        "expired" => values.contains(&(!over_ride["expired_date"].is_null()).to_string()),
+        "releases" => values.iter().any(|value| value == release_of(&text(&over_ride["nvr"]))),
+        "user" => values.contains(&text(&over_ride["submitter"]["name"])),
+        _ => true,
+    })
+}
+
+fn matches_build(build: &Value, update: Option<&str>, params: &HashMap<String, Vec<String>>) -> bool {
+    let nvr = text(&build["nvr"]);
+
+    params.iter().all(|(key, values)| match key.as_str() {
+        "nvr" => values.contains(&nvr),
+        "packages" => values.iter().any(|value| value == package_name(&nvr)),
+        "releases" => values.iter().any(|value| value == release_of(&nvr)),
+        "updates" => update.is_some_and(|alias| values.iter().any(|value| value == alias)),
+        _ => true,
+    })
+}
+
+fn matches_package(package: &Value, params: &HashMap<String, Vec<String>>) -> bool {
+    let name = text(&package["name"]);
+
+    params.iter().all(|(key, values)| match key.as_str() {
+        "like" => name.contains(values[0].as_str()),
+        "name" => name == values[0],
+        "search" => name.to_lowercase().contains(&values[0].to_lowercase()),
+        _ => true,
+    })
+}
+
+fn matches_user(user: &Value, params: &HashMap<String, Vec<String>>) -> bool {
+    let name = text(&user["name"]);
+
+    params.iter().all(|(key, values)| match key.as_str() {
+        "groups" => user["groups"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|group| values.contains(&text(&group["name"]))),
+        "like" => name.contains(values[0].as_str()),
+        "name" => name == values[0],
+        _ => true,
+    })
+}
+
+fn matches_comment(comment: &Value, params: &HashMap<String, Vec<String>>) -> bool {
+    let update = &comment["update"];
+
+    params.iter().all(|(key, values)| match key.as_str() {
+        "ignore_users" => !values.contains(&text(&comment["user"]["name"])),
+        "packages" => update["builds"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|build| values.iter().any(|value| value == package_name(&text(&build["nvr"])))),
+        "since" => comment["timestamp"]
+            .as_str()
+            .is_some_and(|date| date >= values[0].as_str()),
+        "update_owners" => values.contains(&text(&update["user"]["name"])),
+        "updates" => values.contains(&text(&update["alias"])),
+        "user" => values.contains(&text(&comment["user"]["name"])),
+        _ => true,
+    })
+}
+
+/// This function returns a single page of results in the format that is used by bodhi.
+fn paginate(key: &str, items: Vec<Value>, params: &HashMap<String, Vec<String>>) -> Response {
+    let param = |name: &str, default: usize| {
+        params
+            .get(name)
+            .and_then(|values| values[0].parse::<usize>().ok())
+            .unwrap_or(default)
+            .max(1)
+    };
+
+    let page = param("page", 1);
+    let rows = param("rows_per_page", 20);
+    let pages = items.len().div_ceil(rows).max(1);
+
+    let slice: Vec<Value> = items.iter().skip((page - 1) * rows).take(rows).cloned().collect();
+
+    Response::json(
+        200,
+        json!({
+            key: slice,
+            "page": page,
+            "pages": pages,
+            "rows_per_page": rows,
+            "total": items.len(),
+        }),
+    )
+}
+
+fn handle_get(state: &State, request: &Request) -> Response {
+    let mut params = request.query.clone();
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+
+    let page = params.get("page").and_then(|values| values[0].parse::<usize>().ok());
+    if page.is_some() && page == state.fail_page {
+        return Response::error(500, "page", "Internal server error.");
+    }
+
+    match segments.as_slice() {
+        ["login"] => Response::json(200, json!({})),
+        ["csrf"] => Response::json(200, json!({ "csrf_token": CSRF_TOKEN })),
+        ["builds"] => {
+            let items = state
+                .builds()
+                .into_iter()
+                .filter(|(build, update)| matches_build(build, update.as_deref(), &params))
+                .map(|(build, _)| build)
+                .collect();
+            paginate("builds", items, &params)
+        },
+        ["builds", nvr] => match state.builds().into_iter().find(|(build, _)| build["nvr"] == *nvr) {
+            Some((build, _)) => Response::json(200, build),
+            None => Response::not_found(),
+        },
+        ["updates"] | ["updates", ""] => {
+            let items = state
+                .updates
+                .iter()
+                .filter(|update| matches_update(update, &params))
+                .cloned()
+                .collect();
+            paginate("updates", items, &params)
+        },
+        ["updates", alias] => match state.update(alias) {
+            Some(update) => Response::json(200, json!({ "update": update, "can_edit": true })),
+            None => Response::not_found(),
+        },
+        ["overrides"] => {
+            let items = state
+                .overrides
+                .iter()
+                .filter(|over_ride| matches_override(over_ride, &params))
+                .cloned()
+                .collect();
+            paginate("overrides", items, &params)
+        },
+        ["overrides", nvr] => match state.over_ride(nvr) {
+            Some(over_ride) => Response::json(200, json!({ "override": over_ride })),
+            None => Response::not_found(),
+        },
+        ["packages"] => {
+            let items = state
+                .packages()
+                .into_iter()
+                .filter(|package| matches_package(package, &params))
+                .collect();
+            paginate("packages", items, &params)
+        },
+        ["users"] => {
+            let items = state
+                .users()
+                .into_iter()
+                .filter(|user| matches_user(user, &params))
+                .collect();
+            paginate("users", items, &params)
+        },
+        ["users", name] => match state.users().into_iter().find(|user| user["name"] == *name) {
+            Some(user) => Response::json(200, json!({ "user": user })),
+            None => Response::not_found(),
+        },
+        ["releases"] => {
+            params.remove("exclude_archived");
+            paginate("releases", state.releases.clone(), &params)
+        },
+        ["releases", name] => match state.releases.iter().find(|release| release["name"] == *name) {
+            Some(release) => Response::json(200, release.clone()),
+            None => Response::not_found(),
+        },
+        ["composes"] => Response::json(200, json!({ "composes": state.composes })),
+        ["composes", release, request] => {
+            let compose = state
+                .composes
+                .iter()
+                .find(|compose| compose["release"]["name"] == *release && compose["request"] == *request);
+
+            match compose {
+                Some(compose) => Response::json(200, json!({ "compose": compose })),
+                None => Response::not_found(),
+            }
+        },
+        ["comments", id] => match state.comments().into_iter().find(|comment| text(&comment["id"]) == *id) {
+            Some(comment) => Response::json(200, json!({ "comment": comment })),
+            None => Response::not_found(),
+        },
+        ["comments"] => {
+            let items = state
+                .comments()
+                .into_iter()
+                .filter(|comment| matches_comment(comment, &params))
+                .collect();
+            paginate("comments", items, &params)
+        },
+        _ => Response::not_found(),
+    }
+}
+
+fn with_caveats(value: &Value) -> Value {
+    let mut value = value.clone();
+    value["caveats"] = json!([]);
+    value
+}
+
+fn apply_update_fields(update: &mut Value, body: &Value) {
+    for field in [
+        "autokarma",
+        "autotime",
+        "close_bugs",
+        "display_name",
+        "notes",
+        "require_bugs",
+        "require_testcases",
+        "requirements",
+        "severity",
+        "stable_days",
+        "stable_karma",
+        "suggest",
+        "type",
+        "unstable_karma",
+    ] {
+        if !body[field].is_null() {
+            update[field] = body[field].clone();
+        }
+    }
+
+    if let Some(builds) = body["builds"].as_array() {
+        update["builds"] = builds.iter().map(|nvr| build(&text(nvr))).collect();
+    }
+
+    if let Some(bugs) = body["bugs"].as_array() {
+        update["bugs"] = bugs
+            .iter()
+            .filter_map(|bug| text(bug).parse::<u64>().ok())
+            .map(bug)
+            .collect();
+    }
+}
+
+fn handle_post(state: &mut State, request: &Request) -> Response {
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+
+    // OpenID login flow: the login form, and the endpoint that completes authentication
+    match segments.as_slice() {
+        ["openid"] => {
+            let form = parse_params(&request.body);
+            let field = |name: &str| form.get(name).map(|values| values[0].as_str()).unwrap_or_default();
+
+            if field("username") != USERNAME || field("password") != PASSWORD {
+                return Response {
+                    status: 200,
+                    body: String::from("<html>Invalid username or password.</html>"),
+                    cookie: None,
+                };
+            }
+
+            let host = request.headers.get("host").cloned().unwrap_or_default();
+            let mut response = json!({});
+            for name in [
+                "assoc_handle",
+                "cla.signed_cla",
+                "claimed_id",
+                "identity",
+                "lp.is_member",
+                "mode",
+                "ns",
+                "ns.cla",
+                "ns.lp",
+                "ns.sreg",
+                "op_endpoint",
+                "response_nonce",
+                "sig",
+                "signed",
+                "sreg.email",
+                "sreg.nickname",
+            ] {
+                response[format!("openid.{}", name)] = json!("mock");
+            }
+            response["openid.return_to"] = json!(format!("http://{}/dologin.html", host));
+
+            return Response::json(200, json!({ "success": true, "response": response }));
+        },
+        ["dologin.html"] => {
+            let mut response = Response::json(200, json!({}));
+            response.cookie = Some(SESSION_COOKIE);
+            return response;
+        },
+        _ => {},
+    }
+
+    let authenticated = request
+        .headers
+        .get("cookie")
+        .is_some_and(|cookie| cookie.contains(SESSION_COOKIE));
+
+    if !authenticated {
+        return Response::error(403, "session", "You must be logged in.");
+    }
+
+    let body: Value = match serde_json::from_str(&request.body) {
+        Ok(body) => body,
+        Err(_) => return Response::error(400, "body", "Invalid JSON body."),
+    };
+
+    if body["csrf_token"] != CSRF_TOKEN {
+        return Response::error(400, "csrf_token", "CSRF tokens do not match.");
+    }
+
+    match segments.as_slice() {
+        ["comments"] => {
+            let id = state.next_id();
+            let alias = text(&body["update"]);
+            let karma = match &body["karma"] {
+                Value::Number(number) => number.as_i64().unwrap_or_default(),
+                other => text(other).parse().unwrap_or_default(),
+            };
+
+            let update = match state.update_mut(&alias) {
+                Some(update) => update,
+                None => return Response::error(404, "update", "Invalid update."),
+            };
+
+            let update_id: u64 = alias
+                .rsplit('-')
+                .next()
+                .and_then(|id| id.parse().ok())
+                .unwrap_or_default();
+            let mut comment = comment(id, update_id, USERNAME, karma, &text(&body["text"]), NOW);
+
+            // feedback is submitted as "bug_feedback.N.bug_id" and "bug_feedback.N.karma" fields
+            let feedback_karma = |prefix: &str, pos: usize| {
+                text(&body[format!("{}.{}.karma", prefix, pos)])
+                    .parse::<i64>()
+                    .unwrap_or_default()
+            };
+            let mut bug_feedbacks = Vec::new();
+            while let Some(bug_id) = body.get(format!("bug_feedback.{}.bug_id", bug_feedbacks.len())) {
+                let bug_id = text(bug_id).parse().unwrap_or_default();
+                bug_feedbacks.push(bug_feedback(
+                    bug_id,
+                    id,
+                    feedback_karma("bug_feedback", bug_feedbacks.len()),
+                ));
+            }
+            let mut testcase_feedbacks = Vec::new();
+            while let Some(name) = body.get(format!("testcase_feedback.{}.testcase_name", testcase_feedbacks.len())) {
+                let karma = feedback_karma("testcase_feedback", testcase_feedbacks.len());
+                testcase_feedbacks.push(testcase_feedback(&text(name), id, karma));
+            }
+            comment["bug_feedback"] = json!(bug_feedbacks);
+            comment["testcase_feedback"] = json!(testcase_feedbacks);
+
+            update["comments"]
+                .as_array_mut()
+                .expect("update comments must be an array")
+                .push(comment.clone());
+            update["karma"] = json!(update["karma"].as_i64().unwrap_or_default() + karma);
+
+            Response::json(200, json!({ "comment": comment, "caveats": [] }))
+        },
+        ["updates"] => {
+            if !body["edited"].is_null() {
+                let update = match state.update_mut(&text(&body["edited"])) {
+                    Some(update) => update,
+                    None => return Response::error(404, "edited", "Invalid update."),
+                };
+
+                apply_update_fields(update, &body);
+                update["date_modified"] = json!(NOW);
+
+                return Response::json(200, with_caveats(update));
+            }
+
+            let nvrs: Vec<String> = match (body["builds"].as_array(), body["from_tag"].as_str()) {
+                (Some(builds), _) => builds.iter().map(text).collect(),
+                (None, Some(tag)) => vec![format!("{}-1.0-1.fc40", tag)],
+                (None, None) => return Response::error(400, "builds", "You must specify builds or a tag."),
+            };
+            let nvr_refs: Vec<&str> = nvrs.iter().map(|nvr| nvr.as_str()).collect();
+
+            let id = state.next_id();
+            let alias = format!("FEDORA-2024-{:010}", id);
+
+            let mut update = update(&alias, id, &nvr_refs, "pending", USERNAME, NOW);
+            update["bugs"] = json!([]);
+            update["request"] = json!("testing");
+            apply_update_fields(&mut update, &body);
+
+            let response = with_caveats(&update);
+            state.updates.push(update);
+
+            Response::json(200, response)
+        },
+        ["updates", alias, "request"] => match state.update_mut(alias) {
+            Some(update) => {
+                update["request"] = body["request"].clone();
+                Response::json(200, json!({ "update": update }))
+            },
+            None => Response::not_found(),
+        },
+        ["updates", alias, "waive-test-results"] => match state.update_mut(alias) {
+            Some(update) => {
+                update["test_gating_status"] = json!("passed");
+                Response::json(200, json!({ "update": update }))
+            },
+            None => Response::not_found(),
+        },
+        ["overrides"] => {
+            if !body["edited"].is_null() {
+                let over_ride = match state.over_ride_mut(&text(&body["edited"])) {
+                    Some(over_ride) => over_ride,
+                    None => return Response::error(404, "edited", "Invalid override."),
+                };
+
+                over_ride["notes"] = body["notes"].clone();
+                over_ride["expiration_date"] = body["expiration_date"].clone();
+                over_ride["expired_date"] = match body["expired"].as_bool() {
+                    Some(true) => json!(NOW),
+                    _ => Value::Null,
+                };
+
+                return Response::json(200, with_caveats(over_ride));
+            }
+
+            let nvr = text(&body["nvr"]);
+
+            // creating an override for a build with an expired override re-activates it
+            match state.over_ride(&nvr) {
+                Some(existing) if existing["expired_date"].is_null() => {
+                    return Response::error(400, "nvr", "Buildroot override for this build already exists.");
+                },
+                Some(_) => state.overrides.retain(|over_ride| over_ride["nvr"] != nvr.as_str()),
+                None => {},
+            }
+
+            let mut over_ride = over_ride(&nvr, USERNAME, &text(&body["expiration_date"]), None);
+            over_ride["notes"] = body["notes"].clone();
+            over_ride["submission_date"] = json!(NOW);
+
+            let response = with_caveats(&over_ride);
+            state.overrides.push(over_ride);
+
+            Response::json(200, response)
+        },
+        _ => Response::not_found(),
+    }
+}
+
+fn handle(state: &Mutex<State>, mut stream: TcpStream) {
+    let request = match read_request(&mut stream) {
+        Some(request) => request,
+        None => return,
+    };
+
+    let response = {
+        let mut state = state.lock().expect("Poisoned lock!");
+
+        let query: Vec<String> = request
+            .query
+            .iter()
+            .flat_map(|(key, values)| values.iter().map(move |value| format!("{}={}", key, value)))
+            .collect();
+        state
+            .requests
+            .push(format!("{} {}?{}", request.method, request.path, query.join("&")));
+
+        match request.method.as_str() {
+            "GET" => handle_get(&state, &request),
+            "POST" => handle_post(&mut state, &request),
+            _ => Response::not_found(),
+        }
+    };
+
+    write_response(&mut stream, response);
+}
+
+/// handle for a mock bodhi server that is running on a random local port
+pub struct MockBodhi {
+    pub url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockBodhi {
+    /// This method starts a new mock server (pre-populated with some releases, updates, overrides,
+    /// and composes) in a background thread. The server is stopped when the test process exits.
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server.");
+        let url = format!(
+            "http://{}",
+            listener.local_addr().expect("Failed to get mock server address.")
+        );
+
+        let state = Arc::new(Mutex::new(fixtures()));
+        let shared = state.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = shared.clone();
+                thread::spawn(move || handle(&state, stream));
+            }
+        });
+
+        MockBodhi { url, state }
+    }
+
+    pub fn login_url(&self) -> String {
+        format!("{}/openid/", self.url)
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect("Poisoned lock!")
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        self.state().requests.clone()
+    }
+
+    /// This method waits until at least `count` requests that start with the given method and path
+    /// were received, for synchronizing tests with commands that poll the server.
+    pub fn wait_for_requests(&self, prefix: &str, count: usize) {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(30);
+
+        while self.state().request_count(prefix) < count {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "Timed out waiting for request: {}",
+                prefix
+            );
+            thread::sleep(std::time::Duration::from_millis(50));
+        }
+    }
+}
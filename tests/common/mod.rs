@@ -0,0 +1,108 @@
+// not every test binary uses every helper
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+use tempfile::TempDir;
+
+pub mod mock;
+pub use mock::*;
+
+/// isolated environment for running the bodhi-cli binary against a mock bodhi server
+///
+/// Every environment has its own (temporary) home directory, with a configuration file for the
+/// mock user, and its own cache directory for the local database, checkpoints, and cookies.
+pub struct TestEnv {
+    pub home: TempDir,
+    pub mock: MockBodhi,
+    password: String,
+}
+
+impl TestEnv {
+    pub fn new() -> Self {
+        let home = TempDir::new().expect("Failed to create temporary directory.");
+
+        let config = home.path().join(".config");
+        std::fs::create_dir_all(&config).expect("Failed to create configuration directory.");
+        std::fs::write(
+            config.join("fedora.toml"),
+            format!("[FAS]\nusername = \"{}\"\n", USERNAME),
+        )
+        .expect("Failed to write configuration file.");
+
+        TestEnv {
+            home,
+            mock: MockBodhi::start(),
+            password: String::from(PASSWORD),
+        }
+    }
+
+    /// This method changes the password that is used for logging in.
+    pub fn with_password(mut self, password: &str) -> Self {
+        self.password = String::from(password);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        self.home.path()
+    }
+
+    /// This method returns a command for running bodhi-cli with the given arguments against the
+    /// mock server, with all relevant environment variables pointing into the test environment.
+    pub fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_bodhi-cli"));
+
+        command
+            .env("HOME", self.home.path())
+            .env("XDG_CACHE_HOME", self.home.path().join(".cache"))
+            .env("XDG_CONFIG_HOME", self.home.path().join(".config"))
+            .env("BODHI_CLI_TEST_PASSWORD", &self.password)
+            .env_remove("DBUS_SESSION_BUS_ADDRESS")
+            .args(["--bodhi-url", &self.mock.url, "--login-url", &self.mock.login_url()])
+            .args(args);
+
+        command
+    }
+
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.command(args).output().expect("Failed to run bodhi-cli.")
+    }
+
+    /// This method runs bodhi-cli, checks that it succeeded, and returns its standard output.
+    pub fn success(&self, args: &[&str]) -> String {
+        let output = self.run(args);
+
+        assert!(
+            output.status.success(),
+            "bodhi-cli {:?} failed:\n{}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    /// This method runs bodhi-cli, checks that it failed, and returns its error output.
+    pub fn failure(&self, args: &[&str]) -> String {
+        let output = self.run(args);
+
+        assert!(
+            !output.status.success(),
+            "bodhi-cli {:?} succeeded unexpectedly:\n{}",
+            args,
+            String::from_utf8_lossy(&output.stdout)
+        );
+
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    }
+
+    /// This method runs bodhi-cli with JSON output, and returns the parsed output.
+    pub fn json(&self, args: &[&str]) -> serde_json::Value {
+        let mut args = args.to_vec();
+        args.extend(["--format", "json"]);
+
+        let output = self.success(&args);
+        serde_json::from_str(&output).expect("Failed to parse JSON output.")
+    }
+}